@@ -0,0 +1,96 @@
+//! Per keystroke search latency with a large app index held in memory.
+//! Run with `cargo bench --bench search`.
+
+use std::path::PathBuf;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tuicher_lib::{
+    apps::{running::RunningApps, App, AppAction},
+    config::LauncherConfig,
+    history::LaunchHistory,
+    mime::MimeGlobs,
+    search::search,
+    state::AppState,
+};
+use tuicher_rs::config::Config;
+
+const APPS_COUNT: usize = 3000;
+
+/// Config the searches run with, so the bench doesn't depend on the user's own
+const CONFIG_FIXTURE: &str = include_str!("fixtures/config.json");
+
+/// MIME globs for typed paths, so the bench doesn't depend on the installed ones
+const GLOBS_FIXTURE: &str = include_str!("fixtures/globs2");
+
+const WORDS: [&str; 12] = [
+    "Text", "Editor", "Image", "Viewer", "Music", "Player", "Terminal", "Files", "Office",
+    "Browser", "Settings", "Monitor",
+];
+
+fn get_fake_apps() -> Vec<App> {
+    (0..APPS_COUNT)
+        .map(|index| {
+            let name = format!(
+                "{} {} {index}",
+                WORDS[index % WORDS.len()],
+                WORDS[(index / WORDS.len()) % WORDS.len()]
+            );
+
+            App {
+                id: format!("app-{index}.desktop"),
+                name: name.clone(),
+                generic_name: Some(WORDS[(index + 5) % WORDS.len()].to_string()),
+                description: Some(format!("Description of {name}")),
+                keywords: vec![WORDS[(index + 3) % WORDS.len()].to_lowercase()],
+                categories: vec!["Utility".to_string()],
+                mime_types: vec![],
+                path: PathBuf::from(format!("/usr/share/applications/app-{index}.desktop")),
+                exec: Some(format!("app-{index} %U")),
+                startup_wm_class: None,
+                icon_path: None,
+                actions: vec![AppAction {
+                    id: "new-window".to_string(),
+                    name: "New Window".to_string(),
+                    exec: Some(format!("app-{index} --new-window")),
+                    icon_path: None,
+                }],
+                no_display: false,
+            }
+        })
+        .collect()
+}
+
+fn search_benchmark(c: &mut Criterion) {
+    let config: Config =
+        serde_json::from_str(CONFIG_FIXTURE).expect("Failed to parse config fixture");
+
+    let state = AppState::new(
+        get_fake_apps(),
+        config,
+        LauncherConfig::default(),
+        LaunchHistory::default(),
+        MimeGlobs::parse(GLOBS_FIXTURE),
+    )
+    .expect("Failed to create state");
+
+    // Nothing running, so searches don't look at /proc or ask the compositor
+    state.set_running_apps(RunningApps::default());
+
+    let mut group = c.benchmark_group(format!("search {APPS_COUNT} apps"));
+
+    // Every prefix of the query, like the frontend sends while typing
+    let query = "terminal files";
+
+    for end in 1..=query.len() {
+        let text = &query[..end];
+
+        group.bench_function(format!("keystroke '{text}'"), |b| {
+            b.iter(|| search(&state, black_box(text)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, search_benchmark);
+criterion_main!(benches);
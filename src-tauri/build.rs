@@ -1,3 +1,23 @@
+use std::fs;
+
+/// File with the types stored in `apps.bin`. Its hash is written in the index header so an
+/// index from a build with different types gets rebuilt instead of failing to decode.
+const APPS_SCHEMA_PATH: &str = "src/apps/schema.rs";
+
 fn main() {
+    println!("cargo:rerun-if-changed={APPS_SCHEMA_PATH}");
+
+    let schema = fs::read(APPS_SCHEMA_PATH).expect("Failed to read apps schema");
+    println!(
+        "cargo:rustc-env=TUICHER_APPS_SCHEMA_HASH={}",
+        fnv1a(&schema)
+    );
+
     tauri_build::build()
 }
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -1,21 +1,98 @@
 use std::{
     error::Error,
-    process::{Command, Stdio},
+    iter,
+    path::{Path, PathBuf},
     thread,
+    time::Duration,
 };
 
-use tauri::{AppHandle, WebviewUrl, WebviewWindowBuilder, Window};
-use tuicher_rs::result::{Action, CopyImage, CopyText, OpenApp, OpenURL};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Window};
+use tuicher_rs::result::{Action, CopyImage, CopyText, OpenApp, OpenFile, OpenURL, ShowResults};
 
-use crate::plugins::{bookmarks::on_bookmark_action, session::on_session_action};
+use crate::{
+    clipboard::{copy_files, copy_image, copy_text},
+    config::{write_launcher_config, LaunchProfile, LauncherConfig},
+    history::record_app_launch,
+    launcher::{launch_app, process::spawn_detached},
+    mime::{get_file_mime_type, query_default_app, MimeApps},
+    result::{
+        CopyFile, LauncherAction, OpenAppWithArgs, OpenAppWithProfile, OpenDesktopAction, OpenWith,
+        ResultAction, SearchResult, SetDefaultProfile, ShowInFolder, TypeMode, TypeText,
+    },
+    search::show_navigation_level,
+    state::AppState,
+    typing::{paste_text, type_text},
+    utils::get_file_uri,
+};
+
+/// How long focus takes to go back to the previous window once the launcher hides
+const FOCUS_DELAY: Duration = Duration::from_millis(150);
+
+/// Runs off the main thread, since some actions wait for the window to hide
+#[tauri::command(async)]
+pub fn invoke_result_action(
+    action: ResultAction,
+    app: AppHandle,
+    window: Window,
+) -> Result<(), String> {
+    let state = window.state::<AppState>();
+
+    for provider in &state.providers {
+        if let Some(result) = provider.handle_action(&action, &window) {
+            return result.map_err(|e| e.to_string());
+        }
+    }
+
+    match action {
+        ResultAction::Core(action) => on_core_action(action, app, window),
+        ResultAction::Launcher(action) => on_launcher_action(action, window),
+    }
+}
 
-#[tauri::command]
-pub fn invoke_result_action(action: Action, app: AppHandle, window: Window) -> Result<(), String> {
+fn on_launcher_action(action: LauncherAction, window: Window) -> Result<(), String> {
+    match action {
+        LauncherAction::OpenDesktopAction(open_desktop_action) => {
+            on_open_desktop_action(open_desktop_action, window.clone())
+                .map_err(|e| e.to_string())?;
+        }
+        LauncherAction::OpenWith(open_with) => {
+            on_open_with(open_with, window.clone()).map_err(|e| e.to_string())?;
+        }
+        LauncherAction::OpenAppWithArgs(open_app_with_args) => {
+            on_open_app_with_args(open_app_with_args, window.clone()).map_err(|e| e.to_string())?;
+        }
+        LauncherAction::OpenAppWithProfile(open_app_with_profile) => {
+            on_open_app_with_profile(open_app_with_profile, window.clone())
+                .map_err(|e| e.to_string())?;
+        }
+        LauncherAction::SetDefaultProfile(set_default_profile) => {
+            on_set_default_profile(set_default_profile, window.clone())
+                .map_err(|e| e.to_string())?;
+        }
+        LauncherAction::ShowInFolder(show_in_folder) => {
+            on_show_in_folder(show_in_folder, window.clone()).map_err(|e| e.to_string())?;
+        }
+        LauncherAction::CopyFile(copy_file) => {
+            on_copy_file(copy_file, window.clone()).map_err(|e| e.to_string())?;
+        }
+        LauncherAction::TypeText(type_text) => {
+            on_type_text(type_text, window.clone()).map_err(|e| e.to_string())?;
+        }
+        // Handled by the search providers
+        _ => return Err("No provider handles this action".to_string()),
+    }
+
+    Ok(())
+}
+
+fn on_core_action(action: Action, app: AppHandle, window: Window) -> Result<(), String> {
     match action {
         Action::OpenApp(open_app) => {
             on_open_app(open_app, window.clone()).map_err(|e| e.to_string())?;
         }
-        Action::OpenFile(_open_file) => {}
+        Action::OpenFile(open_file) => {
+            on_open_file(open_file, window.clone()).map_err(|e| e.to_string())?;
+        }
         Action::OpenURL(open_url) => {
             on_open_url(open_url, window.clone()).map_err(|e| e.to_string())?;
         }
@@ -25,76 +102,339 @@ pub fn invoke_result_action(action: Action, app: AppHandle, window: Window) -> R
         Action::CopyImage(copy_image) => {
             on_copy_image(copy_image, window.clone()).map_err(|e| e.to_string())?;
         }
-        Action::ShowResults(_show_results) => {}
+        Action::ShowResults(show_results) => {
+            on_show_results(show_results, window.clone()).map_err(|e| e.to_string())?;
+        }
         Action::OpenSettings => {
             on_open_settings(app.clone(), window.clone()).map_err(|e| e.to_string())?;
         }
-        Action::Session(session) => {
-            on_session_action(session, window.clone()).map_err(|e| e.to_string())?;
-        }
-        Action::Bookmark(bookmark) => {
-            on_bookmark_action(bookmark, window.clone()).map_err(|e| e.to_string())?;
-        }
+        // Handled by the search providers
+        _ => return Err("No provider handles this action".to_string()),
     }
 
     Ok(())
 }
 
 fn on_open_app(action: OpenApp, window: Window) -> Result<(), Box<dyn Error>> {
-    let file_name = action
-        .path
-        .clone()
-        .file_name()
-        .ok_or_else(|| "Failed to get file name")?
-        .to_owned();
-
-    thread::spawn(move || {
-        Command::new("gtk-launch")
-            .arg(file_name)
-            .spawn()
-            .expect("Failed to open app");
-    });
+    let profile = get_default_profile(&action.path, &window);
+    let launcher_config = window.state::<AppState>().launcher_config();
+
+    launch_app(&action.path, None, &[], profile.as_ref(), &launcher_config)?;
+
+    remember_launch(&action.path, &window)?;
 
     window.close()?;
 
     Ok(())
 }
 
+fn on_open_desktop_action(action: OpenDesktopAction, window: Window) -> Result<(), Box<dyn Error>> {
+    let profile = get_default_profile(&action.path, &window);
+    let launcher_config = window.state::<AppState>().launcher_config();
+
+    launch_app(
+        &action.path,
+        Some(&action.action),
+        &action.targets,
+        profile.as_ref(),
+        &launcher_config,
+    )?;
+
+    remember_launch(&action.path, &window)?;
+
+    window.close()?;
+
+    Ok(())
+}
+
+fn on_open_with(action: OpenWith, window: Window) -> Result<(), Box<dyn Error>> {
+    let file = action.file.display().to_string();
+
+    let profile = get_default_profile(&action.path, &window);
+    let launcher_config = window.state::<AppState>().launcher_config();
+
+    launch_app(
+        &action.path,
+        None,
+        &[file],
+        profile.as_ref(),
+        &launcher_config,
+    )?;
+
+    remember_launch(&action.path, &window)?;
+
+    window.close()?;
+
+    Ok(())
+}
+
+fn on_open_app_with_args(action: OpenAppWithArgs, window: Window) -> Result<(), Box<dyn Error>> {
+    let profile = get_default_profile(&action.path, &window);
+    let launcher_config = window.state::<AppState>().launcher_config();
+
+    launch_app(
+        &action.path,
+        None,
+        &action.targets,
+        profile.as_ref(),
+        &launcher_config,
+    )?;
+
+    remember_launch(&action.path, &window)?;
+
+    window.close()?;
+
+    Ok(())
+}
+
+fn on_open_app_with_profile(
+    action: OpenAppWithProfile,
+    window: Window,
+) -> Result<(), Box<dyn Error>> {
+    let launcher_config = window.state::<AppState>().launcher_config();
+
+    let profile = match &action.profile {
+        Some(name) => Some(
+            launcher_config
+                .get_profile(name)
+                .ok_or_else(|| format!("Unknown launch profile {name}"))?,
+        ),
+        None => None,
+    };
+
+    launch_app(&action.path, None, &[], profile, &launcher_config)?;
+
+    remember_launch(&action.path, &window)?;
+
+    window.close()?;
+
+    Ok(())
+}
+
+fn on_set_default_profile(action: SetDefaultProfile, window: Window) -> Result<(), Box<dyn Error>> {
+    if let Some(name) = &action.profile {
+        let launcher_config = window.state::<AppState>().launcher_config();
+
+        let profile = launcher_config
+            .get_profile(name)
+            .ok_or_else(|| format!("Unknown launch profile {name}"))?;
+
+        if profile.pkexec {
+            return Err("Profiles that run as root can't be defaults".into());
+        }
+    }
+
+    remember_profile(&action.path, action.profile, &window)?;
+
+    window.close()?;
+
+    Ok(())
+}
+
+/// The default profile of the app at `path`
+fn get_default_profile(path: &Path, window: &Window) -> Option<LaunchProfile> {
+    let state = window.state::<AppState>();
+    let apps = state.apps();
+    let app = apps.iter().find(|app| app.path == path)?;
+
+    state.launcher_config().get_app_profile(&app.id).cloned()
+}
+
+/// Makes `profile` the default of the app at `path`, or forgets its default when `None`
+fn remember_profile(
+    path: &Path,
+    profile: Option<String>,
+    window: &Window,
+) -> Result<(), Box<dyn Error>> {
+    let state = window.state::<AppState>();
+    let apps = state.apps();
+
+    let app = apps
+        .iter()
+        .find(|app| app.path == path)
+        .ok_or_else(|| "App is not in the index")?;
+
+    let mut launcher_config = (*state.launcher_config()).clone();
+
+    let changed = match profile {
+        Some(profile) => {
+            launcher_config
+                .app_profiles
+                .insert(app.id.clone(), profile.clone())
+                != Some(profile)
+        }
+        None => launcher_config.app_profiles.remove(&app.id).is_some(),
+    };
+
+    if changed {
+        write_launcher_config(&launcher_config)?;
+        state.set_launcher_config(launcher_config);
+    }
+
+    Ok(())
+}
+
+fn remember_launch(path: &Path, window: &Window) -> Result<(), Box<dyn Error>> {
+    let history = record_app_launch(path)
+        .map_err(|e| format!("Failed to record the launch in the history: {e}"))?;
+
+    window.state::<AppState>().set_history(history);
+    Ok(())
+}
+
 fn on_open_url(action: OpenURL, window: Window) -> Result<(), Box<dyn Error>> {
-    open::that_detached(&action.url)?;
+    open_detached(&action.url, &window.state::<AppState>().launcher_config())?;
     window.close()?;
     Ok(())
 }
 
-fn on_copy_text(action: CopyText, window: Window) -> Result<(), Box<dyn Error>> {
-    thread::spawn(move || {
-        let text_parts: Vec<&str> = action.text.split(" ").collect();
+/// Opens the file with the default app of its type, or the system opener when no app in the
+/// index is the default
+fn on_open_file(action: OpenFile, window: Window) -> Result<(), Box<dyn Error>> {
+    let file = Path::new(&action.path);
+    let app_path = get_default_app_path(&window.state::<AppState>(), file);
+
+    match app_path {
+        Some(app_path) => {
+            let profile = get_default_profile(&app_path, &window);
+            let launcher_config = window.state::<AppState>().launcher_config();
+
+            launch_app(
+                &app_path,
+                None,
+                &[file.display().to_string()],
+                profile.as_ref(),
+                &launcher_config,
+            )?;
+
+            remember_launch(&app_path, &window)?;
+        }
+        None => open_detached(
+            &file.display().to_string(),
+            &window.state::<AppState>().launcher_config(),
+        )?,
+    }
+
+    window.close()?;
+    Ok(())
+}
+
+/// Desktop file of the default app for the file, from `mimeapps.list` first and `xdg-mime`
+/// after
+fn get_default_app_path(state: &AppState, file: &Path) -> Option<PathBuf> {
+    let mime_type = get_file_mime_type(&state.matchers.mime_globs, file);
+    let apps = state.apps();
 
-        Command::new("wl-copy")
-            .args(text_parts)
-            .spawn()
-            .expect("Error copying to clipboard");
-    });
+    MimeApps::load()
+        .get_default(&mime_type)
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        // xdg-mime is only run when mimeapps.list has no default in the index
+        .chain(iter::once_with(|| query_default_app(&mime_type)).flatten())
+        .find_map(|id| apps.iter().find(|app| app.id == id))
+        .map(|app| app.path.clone())
+}
+
+/// Shows the file selected in the file manager, or opens its folder when no file manager
+/// answers over D-Bus
+fn on_show_in_folder(action: ShowInFolder, window: Window) -> Result<(), Box<dyn Error>> {
+    if show_items(&action.path).is_err() {
+        let folder = if action.path.is_dir() {
+            action.path.as_path()
+        } else {
+            action
+                .path
+                .parent()
+                .ok_or_else(|| "File has no parent folder")?
+        };
+
+        open_detached(
+            &folder.display().to_string(),
+            &window.state::<AppState>().launcher_config(),
+        )?;
+    }
+
+    window.close()?;
+    Ok(())
+}
+
+/// Calls `ShowItems` of the `org.freedesktop.FileManager1` interface
+fn show_items(path: &Path) -> Result<(), Box<dyn Error>> {
+    let connection = zbus::blocking::Connection::session()?;
+
+    connection.call_method(
+        Some("org.freedesktop.FileManager1"),
+        "/org/freedesktop/FileManager1",
+        Some("org.freedesktop.FileManager1"),
+        "ShowItems",
+        &(vec![get_file_uri(path)], ""),
+    )?;
+
+    Ok(())
+}
+
+/// Opens a url or file with the first opener command that can be started
+pub fn open_detached(target: &str, launcher_config: &LauncherConfig) -> Result<(), Box<dyn Error>> {
+    let mut last_error: Box<dyn Error> = "No opener found".into();
+
+    for command in open::commands(target) {
+        match spawn_detached(command, "open", launcher_config) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_error = e,
+        }
+    }
 
+    Err(last_error)
+}
+
+fn on_copy_text(action: CopyText, window: Window) -> Result<(), Box<dyn Error>> {
+    copy_text(&action.text)?;
     window.close()?;
     Ok(())
 }
 
-fn on_copy_image(copy_image: CopyImage, window: Window) -> Result<(), Box<dyn Error>> {
-    thread::spawn(move || {
-        Command::new("cat")
-            .arg(format!("'{}'", copy_image.path.display()))
-            .args(["|", "wl-copy", "-t", "image/png"])
-            .stdout(Stdio::piped())
-            .stdin(Stdio::piped())
-            .spawn()
-            .expect("Error copying image");
-    });
+fn on_copy_file(action: CopyFile, window: Window) -> Result<(), Box<dyn Error>> {
+    copy_files(&[action.path])?;
+    window.close()?;
+    Ok(())
+}
 
+/// Hides the window first, so the text goes to the window that was focused before it. The
+/// window comes back to show the error when typing fails.
+fn on_type_text(action: TypeText, window: Window) -> Result<(), Box<dyn Error>> {
+    window.close()?;
+    thread::sleep(FOCUS_DELAY);
+
+    let typed = match action.mode {
+        TypeMode::Type => type_text(&action.text),
+        TypeMode::Paste => paste_text(&action.text),
+    };
+
+    if typed.is_err() {
+        window.show()?;
+    }
+
+    typed
+}
+
+fn on_copy_image(action: CopyImage, window: Window) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(&action.path);
+    let mime_type = get_file_mime_type(&window.state::<AppState>().matchers.mime_globs, path);
+
+    copy_image(path, &mime_type)?;
     window.close()?;
     Ok(())
 }
 
+/// Opens the results as a new navigation level, keeping the window open
+fn on_show_results(action: ShowResults, window: Window) -> Result<(), Box<dyn Error>> {
+    let results = action.results.into_iter().map(SearchResult::from).collect();
+
+    window.state::<AppState>().push_navigation(results);
+    show_navigation_level(&window)
+}
+
 fn on_open_settings(app: AppHandle, window: Window) -> Result<(), Box<dyn Error>> {
     WebviewWindowBuilder::new(
         &app,
@@ -0,0 +1,234 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::PathBuf,
+};
+
+use bincode::{config, Decode, Encode};
+use freedesktop_desktop_entry::{get_languages_from_env, DesktopEntry};
+use tux_icons::icon_fetcher::IconFetcher;
+
+use crate::apps::{
+    get_cache_dir,
+    resolve::{
+        get_application_dirs, get_current_desktops, get_desktop_file_id, is_available,
+        is_desktop_file, resolve_desktop_files, resolve_entries,
+    },
+    App, AppAction,
+};
+
+/// Resolved icon paths by icon name. Looking up icons in the themes is the slowest part of
+/// indexing, so the results are kept between runs.
+#[derive(Encode, Decode, Debug, Clone, Default)]
+pub struct IconCache {
+    icons: HashMap<String, Option<PathBuf>>,
+}
+
+impl IconCache {
+    /// Loads the cached icons. Misses and icons that were removed are dropped so they get
+    /// looked up again.
+    pub fn load() -> Self {
+        let cache = get_icon_cache_path()
+            .and_then(|path| Ok(fs::read(path)?))
+            .and_then(|bytes| {
+                let (cache, _): (IconCache, usize) =
+                    bincode::decode_from_slice(&bytes, config::standard())?;
+
+                Ok(cache)
+            });
+
+        let mut cache = cache.unwrap_or_default();
+
+        cache
+            .icons
+            .retain(|_, path| path.as_ref().is_some_and(|path| path.exists()));
+
+        cache
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let bytes = bincode::encode_to_vec(self, config::standard())?;
+        fs::write(get_icon_cache_path()?, &bytes)?;
+
+        Ok(())
+    }
+
+    pub fn get_icon_path(&mut self, icon_fetcher: &IconFetcher, icon: &str) -> Option<PathBuf> {
+        self.icons
+            .entry(icon.to_string())
+            .or_insert_with(|| icon_fetcher.get_icon_path(icon))
+            .clone()
+    }
+}
+
+/// Indexes the desktop entries that should be shown from every application dir
+pub fn index_all_apps() -> Result<Vec<App>, Box<dyn Error>> {
+    let mut icon_cache = IconCache::load();
+    let icon_fetcher = IconFetcher::new().set_return_target_path(true);
+    let locales = get_languages_from_env();
+
+    let apps: Vec<App> =
+        resolve_entries(&get_application_dirs(), &get_current_desktops(), &locales)
+            .into_iter()
+            .filter_map(|(id, entry)| {
+                parse_app(id, &entry, &locales, &icon_fetcher, &mut icon_cache)
+            })
+            .collect();
+
+    icon_cache.save()?;
+
+    Ok(apps)
+}
+
+/// Re-parses only the desktop file IDs affected by the changed paths. Paths can be desktop
+/// files or whole directories that were added or removed.
+pub fn update_apps(apps: Vec<App>, changed_paths: &HashSet<PathBuf>) -> Vec<App> {
+    let mut icon_cache = IconCache::load();
+    let icon_fetcher = IconFetcher::new().set_return_target_path(true);
+    let locales = get_languages_from_env();
+    let application_dirs = get_application_dirs();
+    let desktops = get_current_desktops();
+
+    // Listing the dirs is cheap, it's parsing and icon lookups that aren't
+    let files = resolve_desktop_files(&application_dirs);
+    let mut changed_ids: HashSet<String> = HashSet::new();
+
+    for changed_path in changed_paths {
+        changed_ids.extend(
+            apps.iter()
+                .filter(|app| app.path.starts_with(changed_path))
+                .map(|app| app.id.clone()),
+        );
+
+        changed_ids.extend(
+            files
+                .iter()
+                .filter(|(_, path)| path.starts_with(changed_path))
+                .map(|(id, _)| id.clone()),
+        );
+
+        // A removed override makes the ID fall back to a file in another dir
+        if is_desktop_file(changed_path) {
+            changed_ids.extend(get_desktop_file_id(changed_path, &application_dirs));
+        }
+    }
+
+    let mut apps: Vec<App> = apps
+        .into_iter()
+        .filter(|app| !changed_ids.contains(&app.id))
+        .collect();
+
+    for id in changed_ids {
+        let Some(path) = files.get(&id) else {
+            continue;
+        };
+
+        let Ok(entry) = DesktopEntry::from_path(path, Some(&locales)) else {
+            continue;
+        };
+
+        if !is_available(&entry, &desktops) {
+            continue;
+        }
+
+        if let Some(app) = parse_app(id, &entry, &locales, &icon_fetcher, &mut icon_cache) {
+            apps.push(app);
+        }
+    }
+
+    let _ = icon_cache.save();
+
+    apps
+}
+
+fn parse_app(
+    id: String,
+    entry: &DesktopEntry,
+    locales: &[String],
+    icon_fetcher: &IconFetcher,
+    icon_cache: &mut IconCache,
+) -> Option<App> {
+    let name = entry.name(locales)?.to_string();
+
+    let generic_name = entry
+        .generic_name(locales)
+        .map(|generic_name| generic_name.to_string());
+
+    let description = match entry.comment(locales) {
+        Some(description) => Some(description.to_string()),
+        None => None,
+    };
+
+    let keywords: Vec<String> = match entry.keywords(locales) {
+        Some(keywords) => keywords.into_iter().map(|key| key.to_string()).collect(),
+        None => vec![],
+    };
+
+    let categories = get_list(entry, "Categories");
+    let mime_types = get_list(entry, "MimeType");
+
+    let icon_path = if let Some(icon) = entry.icon() {
+        icon_cache.get_icon_path(icon_fetcher, icon)
+    } else {
+        None
+    };
+
+    let actions: Vec<AppAction> = entry
+        .actions()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|action| {
+            let name = entry.action_name(action, locales)?.to_string();
+
+            let icon_path = match entry.action_entry(action, "Icon") {
+                Some(icon) => icon_cache.get_icon_path(icon_fetcher, icon),
+                None => None,
+            };
+
+            Some(AppAction {
+                id: action.to_string(),
+                name,
+                exec: entry.action_exec(action).map(|exec| exec.to_string()),
+                icon_path,
+            })
+        })
+        .collect();
+
+    Some(App {
+        id,
+        name,
+        generic_name,
+        description,
+        keywords,
+        categories,
+        mime_types,
+        path: entry.path.clone(),
+        exec: entry.exec().map(|exec| exec.to_string()),
+        startup_wm_class: entry
+            .desktop_entry("StartupWMClass")
+            .map(|wm_class| wm_class.to_string()),
+        icon_path,
+        actions,
+        no_display: entry.no_display(),
+    })
+}
+
+/// Values of a `;` separated list key
+fn get_list(entry: &DesktopEntry, key: &str) -> Vec<String> {
+    entry
+        .desktop_entry(key)
+        .unwrap_or_default()
+        .split(';')
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+        .collect()
+}
+
+fn get_icon_cache_path() -> Result<PathBuf, Box<dyn Error>> {
+    let mut path = get_cache_dir()?;
+    path.push("icons.bin");
+
+    Ok(path)
+}
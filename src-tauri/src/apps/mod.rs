@@ -0,0 +1,132 @@
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use bincode::config;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    apps::{index::index_all_apps, watcher::watch_apps},
+    state::AppState,
+};
+
+pub mod index;
+pub mod resolve;
+pub mod running;
+pub mod schema;
+pub mod search;
+pub mod watcher;
+
+pub use schema::{App, AppAction};
+
+/// Identifies a tuicher app index file
+const INDEX_MAGIC: [u8; 4] = *b"TUIA";
+
+/// Version of the index file layout. The header is followed by the bincode encoded apps.
+const INDEX_VERSION: u32 = 1;
+
+/// Hash of the index types of this build, computed by build.rs
+const SCHEMA_HASH: &str = env!("TUICHER_APPS_SCHEMA_HASH");
+
+const HEADER_LEN: usize = 4 + 4 + 8;
+
+/// Set when [`get_apps`] rebuilt the index, so the indexing thread doesn't build it again
+static INDEX_REBUILT: AtomicBool = AtomicBool::new(false);
+
+pub fn setup_apps_indexing(app: AppHandle) -> Result<(), Box<dyn Error>> {
+    thread::spawn(move || {
+        // The watcher is started before the first index so no change is missed in between
+        let events = watch_apps().expect("Failed to get watcher");
+
+        let apps = if INDEX_REBUILT.swap(false, Ordering::SeqCst) {
+            (*app.state::<AppState>().apps()).clone()
+        } else {
+            let apps = index_all_apps().unwrap_or_default();
+            let _ = write_apps(&apps);
+            app.state::<AppState>().set_apps(apps.clone());
+            apps
+        };
+
+        events.run(apps, &app);
+    });
+
+    Ok(())
+}
+
+fn get_apps_indexing_path() -> Result<PathBuf, Box<dyn Error>> {
+    let mut path = get_cache_dir()?;
+    path.push("apps.bin");
+
+    Ok(path)
+}
+
+pub(crate) fn get_cache_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let path = dirs::cache_dir()
+        .ok_or_else(|| "Failed to get cache dir")?
+        .join("tuicher");
+
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+
+    Ok(path)
+}
+
+/// Writes the index to a temporary file and renames it over the old one, so readers never see
+/// a partially written index
+pub fn write_apps(apps: &Vec<App>) -> Result<(), Box<dyn Error>> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend_from_slice(&INDEX_MAGIC);
+    bytes.extend_from_slice(&INDEX_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&get_schema_hash().to_le_bytes());
+    bytes.extend(bincode::encode_to_vec(apps, config::standard())?);
+
+    let path = get_apps_indexing_path()?;
+    let temp_path = path.with_extension("bin.tmp");
+
+    fs::write(&temp_path, &bytes)?;
+    fs::rename(&temp_path, &path)?;
+
+    Ok(())
+}
+
+/// Reads the index. A missing, corrupted or outdated index is rebuilt before returning.
+pub fn get_apps() -> Result<Vec<App>, Box<dyn Error>> {
+    if let Ok(apps) = read_apps() {
+        return Ok(apps);
+    }
+
+    let apps = index_all_apps()?;
+    write_apps(&apps)?;
+    INDEX_REBUILT.store(true, Ordering::SeqCst);
+
+    Ok(apps)
+}
+
+fn read_apps() -> Result<Vec<App>, Box<dyn Error>> {
+    let bytes = fs::read(get_apps_indexing_path()?)?;
+
+    if bytes.len() < HEADER_LEN || bytes[0..4] != INDEX_MAGIC {
+        return Err("Not an app index".into());
+    }
+
+    let version = u32::from_le_bytes(bytes[4..8].try_into()?);
+    let schema_hash = u64::from_le_bytes(bytes[8..16].try_into()?);
+
+    if version != INDEX_VERSION || schema_hash != get_schema_hash() {
+        return Err("App index is outdated".into());
+    }
+
+    let (apps, _): (Vec<App>, usize) =
+        bincode::decode_from_slice(&bytes[HEADER_LEN..], config::standard())?;
+
+    Ok(apps)
+}
+
+fn get_schema_hash() -> u64 {
+    SCHEMA_HASH.parse().unwrap_or(0)
+}
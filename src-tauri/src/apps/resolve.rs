@@ -0,0 +1,153 @@
+//! Which desktop entries are shown, following the desktop entry spec
+//! https://specifications.freedesktop.org/desktop-entry-spec/latest/file-naming.html
+
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use freedesktop_desktop_entry::DesktopEntry;
+use walkdir::WalkDir;
+
+use crate::launcher::find_executable;
+
+/// `applications` dirs ordered by precedence: `$XDG_DATA_HOME` first, then `$XDG_DATA_DIRS`
+pub fn get_application_dirs() -> Vec<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local/share")));
+
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.trim().is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut application_dirs: Vec<PathBuf> = vec![];
+
+    for dir in data_home.into_iter().chain(env::split_paths(&data_dirs)) {
+        let dir = dir.join("applications");
+
+        if dir.is_absolute() && !application_dirs.contains(&dir) {
+            application_dirs.push(dir);
+        }
+    }
+
+    application_dirs
+}
+
+/// Desktops from `$XDG_CURRENT_DESKTOP`, matched against `OnlyShowIn` and `NotShowIn`
+pub fn get_current_desktops() -> Vec<String> {
+    env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .map(|desktop| desktop.to_string())
+        .collect()
+}
+
+/// Desktop file ID: the path relative to its applications dir with `/` replaced by `-`
+pub fn get_desktop_file_id(path: &Path, application_dirs: &[PathBuf]) -> Option<String> {
+    let relative_path = application_dirs
+        .iter()
+        .find_map(|dir| path.strip_prefix(dir).ok())?;
+
+    let id = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    Some(id)
+}
+
+/// The desktop file of every ID. When an ID exists in more than one dir the one with the
+/// highest precedence wins, even if it is hidden.
+pub fn resolve_desktop_files(application_dirs: &[PathBuf]) -> HashMap<String, PathBuf> {
+    let mut files: HashMap<String, PathBuf> = HashMap::new();
+
+    for dir in application_dirs {
+        if !dir.exists() {
+            continue;
+        }
+
+        let paths = WalkDir::new(dir)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| path.is_file() && is_desktop_file(path));
+
+        for path in paths {
+            if let Some(id) = get_desktop_file_id(&path, std::slice::from_ref(dir)) {
+                files.entry(id).or_insert(path);
+            }
+        }
+    }
+
+    files
+}
+
+/// Parses the winning desktop file of every ID and keeps the ones that can be used
+pub fn resolve_entries(
+    application_dirs: &[PathBuf],
+    desktops: &[String],
+    locales: &[String],
+) -> Vec<(String, DesktopEntry)> {
+    resolve_desktop_files(application_dirs)
+        .into_iter()
+        .filter_map(|(id, path)| {
+            let entry = DesktopEntry::from_path(path, Some(locales)).ok()?;
+
+            if is_available(&entry, desktops) {
+                Some((id, entry))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Whether an application entry can be used in the current desktops. `NoDisplay` entries can,
+/// since they still open files, and are only left out of app searches.
+pub fn is_available(entry: &DesktopEntry, desktops: &[String]) -> bool {
+    if entry.type_() != Some("Application") {
+        return false;
+    }
+
+    if entry.desktop_entry("Hidden") == Some("true") {
+        return false;
+    }
+
+    let in_desktops = |key: &str| -> Option<bool> {
+        let listed = entry.desktop_entry(key)?;
+
+        Some(listed.split(';').any(|listed_desktop| {
+            desktops
+                .iter()
+                .any(|desktop| desktop.eq_ignore_ascii_case(listed_desktop.trim()))
+        }))
+    };
+
+    if in_desktops("OnlyShowIn") == Some(false) {
+        return false;
+    }
+
+    if in_desktops("NotShowIn") == Some(true) {
+        return false;
+    }
+
+    if let Some(try_exec) = entry.desktop_entry("TryExec") {
+        if !try_exec.is_empty() && find_executable(try_exec).is_none() {
+            return false;
+        }
+    }
+
+    true
+}
+
+pub fn is_desktop_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "desktop")
+}
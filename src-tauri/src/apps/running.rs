@@ -0,0 +1,119 @@
+//! Which apps are running, from the compositor windows or, without a supported compositor,
+//! from the command lines in `/proc`
+
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::{
+    apps::App,
+    compositor::{Compositor, CompositorWindow},
+    launcher::exec::{expand_exec, parse_exec, ExecContext},
+};
+
+/// Programs that run other programs, so a process of one says nothing about which app runs
+const GENERIC_PROGRAMS: [&str; 9] = [
+    "sh",
+    "bash",
+    "env",
+    "flatpak",
+    "snap",
+    "python",
+    "python3",
+    "java",
+    "gtk-launch",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct RunningApps {
+    pub windows: Vec<CompositorWindow>,
+    /// File names of the programs of every process
+    pub programs: HashSet<String>,
+}
+
+impl RunningApps {
+    pub fn load() -> Self {
+        let windows = Compositor::detect()
+            .and_then(|compositor| compositor.get_windows().ok())
+            .unwrap_or_default();
+
+        Self {
+            windows,
+            programs: get_running_programs(),
+        }
+    }
+
+    pub fn get_window(&self, app: &App) -> Option<&CompositorWindow> {
+        self.windows
+            .iter()
+            .find(|window| is_app_window(app, window))
+    }
+
+    pub fn is_running(&self, app: &App) -> bool {
+        if self.get_window(app).is_some() {
+            return true;
+        }
+
+        get_exec_program(app).is_some_and(|program| {
+            !GENERIC_PROGRAMS.contains(&program.as_str()) && self.programs.contains(&program)
+        })
+    }
+}
+
+/// Whether the window belongs to the app. Windows are matched by `StartupWMClass`, the desktop
+/// file ID or the program name against their class.
+pub fn is_app_window(app: &App, window: &CompositorWindow) -> bool {
+    let class = window.class.to_lowercase();
+
+    if class.is_empty() {
+        return false;
+    }
+
+    app.startup_wm_class
+        .as_ref()
+        .is_some_and(|wm_class| wm_class.to_lowercase() == class)
+        || app.id.trim_end_matches(".desktop").to_lowercase() == class
+        || get_exec_program(app).is_some_and(|program| program == class)
+}
+
+/// Lowercase file name of the program the app runs, skipping an `env` prefix
+pub fn get_exec_program(app: &App) -> Option<String> {
+    let exec = parse_exec(app.exec.as_deref()?).ok()?;
+    let command_line = expand_exec(&exec, &ExecContext::default()).concat();
+
+    let program = command_line
+        .iter()
+        .skip_while(|arg| Path::new(arg).file_name().is_some_and(|name| name == "env"))
+        .find(|arg| !arg.contains('='))?;
+
+    Some(
+        Path::new(program)
+            .file_name()?
+            .to_string_lossy()
+            .to_lowercase(),
+    )
+}
+
+fn get_running_programs() -> HashSet<String> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return HashSet::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|entry| fs::read(entry.path().join("cmdline")).ok())
+        .filter_map(|cmdline| {
+            let program = cmdline.split(|byte| *byte == 0).next()?;
+            let program = String::from_utf8_lossy(program);
+
+            // Some programs rewrite their command line into a single string with spaces
+            let program = program.split(' ').next()?;
+
+            Some(
+                Path::new(program)
+                    .file_name()?
+                    .to_string_lossy()
+                    .to_lowercase(),
+            )
+        })
+        .collect()
+}
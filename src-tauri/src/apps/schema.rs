@@ -0,0 +1,40 @@
+//! Types stored in the app index. Any change to this file changes the schema hash in the index
+//! header, which makes existing indexes get rebuilt.
+
+use std::path::PathBuf;
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, Clone)]
+pub struct App {
+    /// Desktop file ID, e.g. `org.mozilla.firefox.desktop`
+    pub id: String,
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Vec<String>,
+    /// Freedesktop categories, e.g. `Graphics` or `WebBrowser`
+    pub categories: Vec<String>,
+    /// MIME types the app can open
+    pub mime_types: Vec<String>,
+    pub path: PathBuf,
+    /// Value of the `Exec` key
+    pub exec: Option<String>,
+    /// Class of the app windows, used to find them when the app is running
+    pub startup_wm_class: Option<String>,
+    pub icon_path: Option<PathBuf>,
+    pub actions: Vec<AppAction>,
+    /// `NoDisplay=true`, for handlers that only open files. They are left out of app searches
+    /// but still open files.
+    pub no_display: bool,
+}
+
+/// A `[Desktop Action <id>]` group of a desktop entry
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, Clone)]
+pub struct AppAction {
+    pub id: String,
+    pub name: String,
+    pub exec: Option<String>,
+    pub icon_path: Option<PathBuf>,
+}
@@ -0,0 +1,461 @@
+use std::{
+    error::Error,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use tuicher_rs::result::{Action, OpenApp};
+
+use crate::{
+    apps::{running::get_exec_program, App},
+    history::now,
+    launcher::exec::{get_target_kind, parse_exec, TargetKind},
+    matcher::fuzzy_match,
+    mime::{get_associated_ids, get_file_mime_type, MimeApps},
+    plugins::SearchProvider,
+    result::{
+        FocusWindow, LauncherAction, OpenAppWithArgs, OpenAppWithProfile, OpenDesktopAction,
+        OpenWith, ResultAction, SearchResult, SetDefaultProfile,
+    },
+    state::AppState,
+    utils::expand_path,
+};
+
+/// Just below an exact app name match, which scores 100 before its frecency bonus
+const OPEN_WITH_ARGS_SCORE: f64 = 95.0;
+
+/// Apps in searches without a keyword. The `cat:` filter and typed paths are handled before
+/// providers are asked.
+pub struct AppsProvider;
+
+impl SearchProvider for AppsProvider {
+    fn id(&self) -> &'static str {
+        "apps"
+    }
+
+    fn default_keyword(&self) -> &'static str {
+        "apps"
+    }
+
+    fn is_global(&self) -> bool {
+        true
+    }
+
+    fn default_max_results(&self) -> usize {
+        10
+    }
+
+    fn query(
+        &self,
+        state: &AppState,
+        query: &str,
+        cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        Ok(get_app_results(state, query, None, cancelled))
+    }
+}
+
+/// Apps and their actions matching `text`, best first. With a category only apps in it are
+/// returned, and an empty text lists all of them. Nothing is returned once `cancelled` is set.
+pub fn get_app_results(
+    state: &AppState,
+    text: &str,
+    category: Option<&str>,
+    cancelled: &AtomicBool,
+) -> Vec<SearchResult> {
+    let history = state.history();
+    let launcher_config = state.launcher_config();
+    let running_apps = state.running_apps();
+    let now = now();
+
+    let mut scored_apps: Vec<(f64, SearchResult)> = vec![];
+
+    for app in state.apps().iter().filter(|app| !app.no_display) {
+        if cancelled.load(Ordering::Relaxed) {
+            return vec![];
+        }
+
+        if let Some(category) = category {
+            let in_category = app
+                .categories
+                .iter()
+                .any(|app_category| app_category.eq_ignore_ascii_case(category));
+
+            if !in_category {
+                continue;
+            }
+        }
+
+        let frecency_score = (1.0 + history.frecency(&app.path, now)).ln() * 10.0;
+
+        if let Some((match_score, highlights)) = get_app_match(app, text) {
+            let secondary_text = app
+                .description
+                .clone()
+                .or_else(|| app.generic_name.clone())
+                .unwrap_or_else(|| "Application".to_string());
+
+            // Apps with a default profile use it until it is cleared
+            let profile = launcher_config.get_app_profile(&app.id);
+
+            let secondary_text = match profile {
+                Some(profile) => format!("{} · {secondary_text}", &profile.name),
+                None => secondary_text,
+            };
+
+            let mut result = SearchResult::new(&app.name, "app")
+                .set_secondary_text(secondary_text)
+                .set_highlights(highlights);
+
+            let launch_action: ResultAction = match profile {
+                Some(profile) => LauncherAction::OpenAppWithProfile(OpenAppWithProfile::new(
+                    &app.path,
+                    Some(profile.name.clone()),
+                ))
+                .into(),
+                None => Action::OpenApp(OpenApp::new(&app.path)).into(),
+            };
+
+            // Running apps are focused, a new instance is one of the secondary actions
+            result = match running_apps.get_window(app) {
+                Some(window) => result
+                    .set_badge("Running")
+                    .set_action(LauncherAction::FocusWindow(FocusWindow::new(&window.id)))
+                    .add_action("New Instance", launch_action),
+                None if running_apps.is_running(app) => {
+                    result.set_badge("Running").set_action(launch_action)
+                }
+                None => result.set_action(launch_action),
+            };
+
+            if profile.is_some() {
+                result = result.add_action(
+                    "Launch Normally",
+                    LauncherAction::OpenAppWithProfile(OpenAppWithProfile::new(&app.path, None)),
+                );
+            }
+
+            for profile in &launcher_config.profiles {
+                result = result.add_action(
+                    &profile.name,
+                    LauncherAction::OpenAppWithProfile(OpenAppWithProfile::new(
+                        &app.path,
+                        Some(profile.name.clone()),
+                    )),
+                );
+            }
+
+            // Running as root is always asked for, never a default
+            let default_candidates = launcher_config.profiles.iter().filter(|candidate| {
+                !candidate.pkexec && profile.is_none_or(|profile| profile.name != candidate.name)
+            });
+
+            for candidate in default_candidates {
+                result = result.add_action(
+                    format!("Set {} as Default", &candidate.name),
+                    LauncherAction::SetDefaultProfile(SetDefaultProfile::new(
+                        &app.path,
+                        Some(candidate.name.clone()),
+                    )),
+                );
+            }
+
+            if profile.is_some() {
+                result = result.add_action(
+                    "Clear Default Profile",
+                    LauncherAction::SetDefaultProfile(SetDefaultProfile::new(&app.path, None)),
+                );
+            }
+
+            if let Some(icon_path) = &app.icon_path {
+                result.set_icon_path(icon_path);
+            }
+
+            scored_apps.push((match_score + frecency_score, result));
+        }
+
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        for app_action in &app.actions {
+            let name = format!("{}: {}", &app.name, &app_action.name);
+
+            // The action name on its own is matched too, its ranges moved past `App: `
+            let action_match = fuzzy_match(&app_action.name, text)
+                .map(|found| found.offset(app.name.chars().count() + 2));
+
+            let found = match (fuzzy_match(&name, text), action_match) {
+                (Some(a), Some(b)) => Some(if a.score >= b.score { a } else { b }),
+                (a, b) => a.or(b),
+            };
+
+            let Some(found) = found else {
+                continue;
+            };
+
+            // Actions rank a bit lower than the app they belong to
+            let score = found.score + frecency_score - 5.0;
+
+            let action = LauncherAction::OpenDesktopAction(OpenDesktopAction::new(
+                &app.path,
+                &app_action.id,
+            ));
+
+            let mut result = SearchResult::new(&name, "app")
+                .set_secondary_text("Application Action")
+                .set_action(action)
+                .set_highlights(found.ranges);
+
+            if let Some(icon_path) = app_action.icon_path.as_ref().or(app.icon_path.as_ref()) {
+                result.set_icon_path(icon_path);
+            }
+
+            scored_apps.push((score, result));
+        }
+    }
+
+    scored_apps.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .total_cmp(a_score)
+            .then_with(|| a.text.to_lowercase().cmp(&b.text.to_lowercase()))
+    });
+
+    scored_apps
+        .into_iter()
+        .map(|(score, result)| result.set_score(score))
+        .collect()
+}
+
+/// "Open with" results for a file, the default app first, then the other associated apps and
+/// finally every app that declares support for the file's MIME type
+pub fn get_open_with_results(state: &AppState, file: &Path) -> Vec<SearchResult> {
+    let mime_type = get_file_mime_type(&state.matchers.mime_globs, file);
+    let mime_apps = MimeApps::load();
+    let apps = state.apps();
+
+    let default_id = mime_apps
+        .get_default(&mime_type)
+        .and_then(|ids| ids.iter().find(|id| apps.iter().any(|app| &app.id == *id)));
+
+    let handlers = get_file_handlers(&apps, &mime_apps, &mime_type);
+
+    let file_name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.display().to_string());
+
+    handlers
+        .into_iter()
+        .map(|app| {
+            let secondary_text = if default_id == Some(&app.id) {
+                format!("Default for {mime_type}")
+            } else {
+                mime_type.clone()
+            };
+
+            let mut result =
+                SearchResult::new(format!("Open {file_name} with {}", &app.name), "app")
+                    .set_secondary_text(secondary_text)
+                    .set_action(LauncherAction::OpenWith(OpenWith::new(&app.path, file)));
+
+            if let Some(icon_path) = &app.icon_path {
+                result.set_icon_path(icon_path);
+            }
+
+            result
+        })
+        .collect()
+}
+
+/// Apps that open the MIME type, the associated ones from `mimeapps.list` and the caches first,
+/// then every app that declares support for it
+pub fn get_file_handlers<'a>(
+    apps: &'a [App],
+    mime_apps: &MimeApps,
+    mime_type: &str,
+) -> Vec<&'a App> {
+    let mut handlers: Vec<&App> = get_associated_ids(mime_type, mime_apps)
+        .iter()
+        .filter_map(|id| apps.iter().find(|app| &app.id == id))
+        .collect();
+
+    for app in apps {
+        let supports_type = app
+            .mime_types
+            .iter()
+            .any(|supported| supported == mime_type);
+
+        if supports_type && !handlers.iter().any(|handler| handler.id == app.id) {
+            handlers.push(app);
+        }
+    }
+
+    handlers
+}
+
+/// "Open <args> with <app>" results for queries like `code ~/projects/api`, where the leading
+/// words name an app and the rest are files or urls for its `Exec` field codes
+pub fn get_app_with_args_results(state: &AppState, text: &str) -> Vec<SearchResult> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let apps = state.apps();
+
+    // The longest app name wins, so `visual studio code file.txt` is not read as `visual`
+    for split in (1..words.len()).rev() {
+        let app_query = words[..split].join(" ");
+        let args = &words[split..];
+
+        let results: Vec<SearchResult> = apps
+            .iter()
+            .filter(|app| !app.no_display && is_app_name(app, &app_query))
+            .filter_map(|app| {
+                let exec = parse_exec(app.exec.as_deref()?).ok()?;
+                let targets = get_targets(state, args, get_target_kind(&exec)?)?;
+
+                // Naming an app and what to open with it is explicit, but an exact app name
+                // match still comes first
+                let mut result =
+                    SearchResult::new(format!("Open {} with {}", args.join(" "), &app.name), "app")
+                        .set_score(OPEN_WITH_ARGS_SCORE)
+                        .set_secondary_text(targets.join(" "))
+                        .set_action(LauncherAction::OpenAppWithArgs(OpenAppWithArgs::new(
+                            &app.path, targets,
+                        )));
+
+                if let Some(icon_path) = &app.icon_path {
+                    result.set_icon_path(icon_path);
+                }
+
+                Some(result)
+            })
+            .collect();
+
+        if !results.is_empty() {
+            return results;
+        }
+    }
+
+    vec![]
+}
+
+/// Whether `text` names the app: its full name, its executable or the last part of its desktop
+/// file ID, e.g. `code`, `firefox` or `nautilus`. Single words of the name don't count, or
+/// `visual studio` would open `studio` with Visual Studio Code.
+fn is_app_name(app: &App, text: &str) -> bool {
+    if app.name.eq_ignore_ascii_case(text) {
+        return true;
+    }
+
+    if get_exec_program(app).is_some_and(|program| program.eq_ignore_ascii_case(text)) {
+        return true;
+    }
+
+    app.id
+        .trim_end_matches(".desktop")
+        .rsplit(['.', '-'])
+        .next()
+        .is_some_and(|id| id.eq_ignore_ascii_case(text))
+}
+
+/// Turns the typed arguments into targets, or `None` when one of them is a file that doesn't
+/// exist or, for apps that take urls, neither a path nor a url. Relative paths are taken from
+/// the home dir.
+fn get_targets(state: &AppState, args: &[&str], kind: TargetKind) -> Option<Vec<String>> {
+    args.iter()
+        .map(|arg| {
+            if kind == TargetKind::Files || is_path_like(arg) {
+                let path = expand_path(arg);
+
+                let path = match dirs::home_dir() {
+                    Some(home) if path.is_relative() => home.join(path),
+                    _ => path,
+                };
+
+                return path.exists().then(|| path.display().to_string());
+            }
+
+            if arg.contains("://") {
+                return Some(arg.to_string());
+            }
+
+            let url = format!("https://{arg}");
+
+            if state.matchers.url_regex.is_match(&url) {
+                Some(url)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn is_path_like(arg: &str) -> bool {
+    arg.starts_with(['/', '~', '$', '.']) || (arg.contains('/') && !arg.contains("://"))
+}
+
+/// Matches on the name rank first, then the generic name, keywords, categories and the file
+/// types the app opens. Only name matches have ranges, since the name is the text of the result.
+fn get_app_match(app: &App, text: &str) -> Option<(f64, Vec<(usize, usize)>)> {
+    if text.trim().is_empty() {
+        return Some((0.0, vec![]));
+    }
+
+    let name_match = fuzzy_match(&app.name, text);
+
+    let generic_score = app
+        .generic_name
+        .as_ref()
+        .and_then(|generic_name| fuzzy_match(generic_name, text))
+        .map(|found| found.score / 2.0);
+
+    // A loose name match shouldn't hide a close generic name match, e.g. `browser`
+    match (name_match, generic_score) {
+        (Some(found), Some(generic_score)) if generic_score > found.score => {
+            return Some((generic_score, vec![]))
+        }
+        (Some(found), _) => return Some((found.score, found.ranges)),
+        (None, Some(generic_score)) => return Some((generic_score, vec![])),
+        (None, None) => {}
+    }
+
+    if app.keywords.iter().any(|key| is_close_match(key, text)) {
+        return Some((20.0, vec![]));
+    }
+
+    if app
+        .categories
+        .iter()
+        .any(|category| is_close_match(category, text))
+    {
+        return Some((10.0, vec![]));
+    }
+
+    if app
+        .mime_types
+        .iter()
+        .any(|mime_type| is_mime_type_match(mime_type, text))
+    {
+        return Some((5.0, vec![]));
+    }
+
+    None
+}
+
+/// The query names the format of the MIME type, e.g. `pdf` for `application/pdf` or `image` for
+/// `image/png`. Only whole names count, partial ones would match most apps.
+fn is_mime_type_match(mime_type: &str, text: &str) -> bool {
+    let text = text.trim().to_lowercase();
+
+    mime_type
+        .to_lowercase()
+        .split(['/', '+'])
+        // Every app opens some `application/*` type
+        .filter(|part| *part != "application")
+        .map(|part| part.trim_start_matches("x-").trim_start_matches("vnd."))
+        .any(|part| part == text)
+}
+
+/// Keywords and categories are many short words, so only the query as a whole counts
+fn is_close_match(name: &str, text: &str) -> bool {
+    fuzzy_match(name, text).is_some_and(|found| found.score >= 45.0)
+}
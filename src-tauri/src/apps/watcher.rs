@@ -0,0 +1,94 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    path::PathBuf,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use notify::{event::ModifyKind, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    apps::{index::update_apps, resolve::get_application_dirs, write_apps, App},
+    state::AppState,
+};
+
+/// How long the application dirs need to be quiet before a batch of changes is indexed
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Longest a batch is held back while changes keep coming, e.g. during a big package update
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(5);
+
+pub struct AppsWatcher {
+    // Events stop arriving once the watcher is dropped
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<Event>>,
+}
+
+pub fn watch_apps() -> Result<AppsWatcher, Box<dyn Error>> {
+    let (tx, rx) = channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx)?;
+
+    for path in get_application_dirs() {
+        if !path.exists() {
+            continue;
+        }
+
+        watcher.watch(&path, RecursiveMode::Recursive)?;
+    }
+
+    Ok(AppsWatcher {
+        _watcher: watcher,
+        rx,
+    })
+}
+
+impl AppsWatcher {
+    /// Blocks forever, re-indexing the changed desktop files of every batch of events
+    pub fn run(self, mut apps: Vec<App>, app: &AppHandle) {
+        while let Some(changed_paths) = self.next_batch() {
+            apps = update_apps(apps, &changed_paths);
+            let _ = write_apps(&apps);
+            app.state::<AppState>().set_apps(apps.clone());
+        }
+    }
+
+    /// Waits for a change and then collects every change that follows until the dirs are quiet
+    fn next_batch(&self) -> Option<HashSet<PathBuf>> {
+        let mut changed_paths: HashSet<PathBuf> = HashSet::new();
+
+        while changed_paths.is_empty() {
+            let event = self.rx.recv().ok()?;
+            changed_paths.extend(get_changed_paths(event));
+        }
+
+        let batch_start = Instant::now();
+
+        while batch_start.elapsed() < MAX_BATCH_DELAY {
+            match self.rx.recv_timeout(DEBOUNCE) {
+                Ok(event) => changed_paths.extend(get_changed_paths(event)),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        Some(changed_paths)
+    }
+}
+
+fn get_changed_paths(event: notify::Result<Event>) -> Vec<PathBuf> {
+    let Ok(event) = event else {
+        return vec![];
+    };
+
+    match event.kind {
+        EventKind::Create(_)
+        | EventKind::Remove(_)
+        | EventKind::Modify(ModifyKind::Data(_))
+        | EventKind::Modify(ModifyKind::Name(_))
+        | EventKind::Modify(ModifyKind::Any) => event.paths,
+        _ => vec![],
+    }
+}
@@ -0,0 +1,60 @@
+//! Clipboard command line tools, used when the protocols can't be spoken directly. They only
+//! take one format, so the first offer is copied.
+
+use std::{
+    error::Error,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::clipboard::Offer;
+
+pub fn wl_copy(offers: &[Offer]) -> Result<(), Box<dyn Error>> {
+    let offer = offers.first().ok_or_else(|| "Nothing to copy")?;
+
+    let mut command = Command::new("wl-copy");
+    command.args(["--type", &offer.mime_type]);
+
+    run_with_input(command, &offer.data)
+}
+
+pub fn xclip(offers: &[Offer]) -> Result<(), Box<dyn Error>> {
+    let offer = offers.first().ok_or_else(|| "Nothing to copy")?;
+
+    let mut command = Command::new("xclip");
+    command.args([
+        "-selection",
+        "clipboard",
+        "-target",
+        &offer.mime_type,
+        "-in",
+    ]);
+
+    run_with_input(command, &offer.data)
+}
+
+/// Writes the data to the command's stdin. Both tools fork to keep serving the selection, so
+/// the command returns once it has read everything. Its output isn't read, since the forked
+/// process would keep the pipes open.
+fn run_with_input(mut command: Command, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Dropped at the end of the statement, closing stdin so the command stops reading
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to get stdin")?
+        .write_all(data)?;
+
+    let status = child.wait()?;
+
+    if !status.success() {
+        return Err(format!("exited with {status}").into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! Copying to and watching the clipboard. The Wayland data-control protocol and X11 selections
+//! are used directly, `wl-copy` and `xclip` only when those fail, e.g. on compositors without
+//! data-control. The selection is served from a background thread of the launcher, so it stays
+//! available after the window hides.
+
+use std::{
+    env,
+    error::Error,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::utils::get_file_uri;
+
+pub mod command;
+pub mod wayland;
+pub mod x11;
+
+/// One of the formats the copied data is offered in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offer {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Offer {
+    pub fn new(mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+}
+
+/// Reads what is on the clipboard
+pub trait ClipboardReader {
+    /// Formats the clipboard is offered in
+    fn mime_types(&self) -> Result<Vec<String>, Box<dyn Error>>;
+
+    fn read(&self, mime_type: &str) -> Result<Vec<u8>, Box<dyn Error>>;
+}
+
+/// Called from a background thread every time something is copied, with an error when the
+/// clipboard can't be read or the watcher stopped
+pub type OnChange = Arc<dyn Fn(Result<&dyn ClipboardReader, Box<dyn Error>>) + Send + Sync>;
+
+type Backend = fn(&[Offer]) -> Result<(), Box<dyn Error>>;
+
+type Watcher = fn(OnChange) -> Result<(), Box<dyn Error>>;
+
+/// How long the clipboard owner gets to answer a read
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Formats that describe the selection instead of holding its data
+const X11_META_TARGETS: [&str; 5] = ["TARGETS", "MULTIPLE", "TIMESTAMP", "SAVE_TARGETS", "DELETE"];
+
+/// Puts the offers on the clipboard, trying every backend of the session in order. The error
+/// lists why each of them failed.
+pub fn set_clipboard(offers: &[Offer]) -> Result<(), Box<dyn Error>> {
+    let mut backends: Vec<(&str, Backend)> = vec![];
+
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        backends.push(("Wayland", wayland::copy));
+        backends.push(("wl-copy", command::wl_copy));
+    }
+
+    if env::var_os("DISPLAY").is_some() {
+        backends.push(("X11", x11::copy));
+        backends.push(("xclip", command::xclip));
+    }
+
+    if backends.is_empty() {
+        return Err("No Wayland or X11 display to copy to".into());
+    }
+
+    let mut errors: Vec<String> = vec![];
+
+    for (name, backend) in backends {
+        match backend(offers) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{name}: {e}")),
+        }
+    }
+
+    Err(format!("Failed to copy to the clipboard ({})", errors.join(", ")).into())
+}
+
+/// Starts watching the clipboard with the first backend of the session that supports it
+pub fn watch_clipboard(on_change: OnChange) -> Result<(), Box<dyn Error>> {
+    let mut watchers: Vec<(&str, Watcher)> = vec![];
+
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        watchers.push(("Wayland", wayland::watch));
+    }
+
+    if env::var_os("DISPLAY").is_some() {
+        watchers.push(("X11", x11::watch));
+    }
+
+    let mut errors: Vec<String> = vec![];
+
+    for (name, watcher) in watchers {
+        match watcher(on_change.clone()) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{name}: {e}")),
+        }
+    }
+
+    Err(format!("Failed to watch the clipboard ({})", errors.join(", ")).into())
+}
+
+/// Everything on the clipboard, in every format that could be read, so it can be put back
+/// with [`set_clipboard`]. Formats whose owner doesn't answer in time are left out.
+pub fn get_clipboard_offers() -> Result<Vec<Offer>, Box<dyn Error>> {
+    let reader: Box<dyn ClipboardReader> = if env::var_os("WAYLAND_DISPLAY").is_some() {
+        Box::new(wayland::WaylandReader)
+    } else if env::var_os("DISPLAY").is_some() {
+        Box::new(x11::create_reader()?)
+    } else {
+        return Err("No Wayland or X11 display to read from".into());
+    };
+
+    let offers = reader
+        .mime_types()?
+        .into_iter()
+        .filter(|mime_type| !X11_META_TARGETS.contains(&mime_type.as_str()))
+        .filter_map(|mime_type| {
+            let data = reader.read(&mime_type).ok()?;
+            Some(Offer::new(mime_type, data))
+        })
+        .collect();
+
+    Ok(offers)
+}
+
+pub fn copy_text(text: &str) -> Result<(), Box<dyn Error>> {
+    set_clipboard(&get_text_offers(text))
+}
+
+/// Copies the image itself, in its own format
+pub fn copy_image(path: &Path, mime_type: &str) -> Result<(), Box<dyn Error>> {
+    if !mime_type.starts_with("image/") {
+        return Err(format!("{} is not an image", path.display()).into());
+    }
+
+    let data = std::fs::read(path)?;
+
+    set_clipboard(&[Offer::new(mime_type, data)])
+}
+
+/// Copies files so file managers can paste them, with their paths as text for everything else
+pub fn copy_files(paths: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    let uris: Vec<String> = paths.iter().map(|path| get_file_uri(path)).collect();
+
+    let paths_text = paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let mut offers = vec![
+        Offer::new("text/uri-list", format!("{}\r\n", uris.join("\r\n"))),
+        Offer::new(
+            "x-special/gnome-copied-files",
+            format!("copy\n{}", uris.join("\n")),
+        ),
+    ];
+
+    offers.extend(get_text_offers(&paths_text));
+
+    set_clipboard(&offers)
+}
+
+/// Text under the names Wayland and X11 clients ask for
+fn get_text_offers(text: &str) -> Vec<Offer> {
+    [
+        "text/plain;charset=utf-8",
+        "text/plain",
+        "UTF8_STRING",
+        "STRING",
+        "TEXT",
+    ]
+    .into_iter()
+    .map(|mime_type| Offer::new(mime_type, text))
+    .collect()
+}
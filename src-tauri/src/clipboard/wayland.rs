@@ -0,0 +1,99 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    io::{BufRead, BufReader, Read},
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+};
+
+use wl_clipboard_rs::{
+    copy::{self, MimeSource, Options, Source},
+    paste::{self, get_contents, get_mime_types, ClipboardType, Seat},
+};
+
+use crate::clipboard::{ClipboardReader, Offer, OnChange, READ_TIMEOUT};
+
+/// Copies through the data-control protocol. The offers are served from a background thread
+/// until another client takes the selection.
+pub fn copy(offers: &[Offer]) -> Result<(), Box<dyn Error>> {
+    let sources = offers
+        .iter()
+        .map(|offer| MimeSource {
+            source: Source::Bytes(offer.data.clone().into_boxed_slice()),
+            mime_type: copy::MimeType::Specific(offer.mime_type.clone()),
+        })
+        .collect();
+
+    Options::new().copy_multi(sources)?;
+
+    Ok(())
+}
+
+/// Reads the clipboard through the data-control protocol
+pub struct WaylandReader;
+
+impl ClipboardReader for WaylandReader {
+    fn mime_types(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mime_types: HashSet<String> =
+            get_mime_types(ClipboardType::Regular, Seat::Unspecified)?;
+
+        Ok(mime_types.into_iter().collect())
+    }
+
+    /// The owner writes the data into a pipe, which is read from a thread of its own so an
+    /// owner that never closes it doesn't block the caller
+    fn read(&self, mime_type: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let (mut pipe, _) = get_contents(
+            ClipboardType::Regular,
+            Seat::Unspecified,
+            paste::MimeType::Specific(mime_type),
+        )?;
+
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut data = vec![];
+            let _ = tx.send(pipe.read_to_end(&mut data).map(|_| data));
+        });
+
+        match rx.recv_timeout(READ_TIMEOUT) {
+            Ok(data) => Ok(data?),
+            Err(_) => Err("Clipboard owner didn't answer".into()),
+        }
+    }
+}
+
+/// Uses `wl-paste --watch` to know when the selection changes, since the protocol can only be
+/// watched by keeping a Wayland event loop running
+pub fn watch(on_change: OnChange) -> Result<(), Box<dyn Error>> {
+    // Compositors without data-control can't be watched, whatever the tool
+    if let Err(e @ paste::Error::MissingProtocol { .. }) =
+        get_mime_types(ClipboardType::Regular, Seat::Unspecified)
+    {
+        return Err(e.into());
+    }
+
+    let mut child = Command::new("wl-paste")
+        .args(["--watch", "echo", "changed"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let stdout = child.stdout.take().ok_or_else(|| "Failed to get stdout")?;
+
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if line.is_err() {
+                break;
+            }
+
+            on_change(Ok(&WaylandReader));
+        }
+
+        let _ = child.wait();
+        on_change(Err("Stopped watching the clipboard".into()));
+    });
+
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use std::{
+    error::Error,
+    thread,
+    time::{Duration, Instant},
+};
+
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::{
+        xfixes::{ConnectionExt as _, SelectionEventMask},
+        xproto::{
+            Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, PropMode,
+            SelectionNotifyEvent, SelectionRequestEvent, Window, WindowClass,
+            SELECTION_NOTIFY_EVENT,
+        },
+        Event,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    COPY_DEPTH_FROM_PARENT, CURRENT_TIME, NONE,
+};
+
+use crate::clipboard::{ClipboardReader, Offer, OnChange, READ_TIMEOUT};
+
+/// Clipboard owned by a hidden window of its own connection
+struct Owner {
+    connection: RustConnection,
+    window: Window,
+    targets: Atom,
+    /// Atom of each offer's MIME type, in the same order
+    atoms: Vec<Atom>,
+    offers: Vec<Offer>,
+}
+
+/// Takes the CLIPBOARD selection and answers requests for it from a background thread, until
+/// another client takes it. Data too large for one request fails, so a tool that supports
+/// incremental transfers is used instead.
+pub fn copy(offers: &[Offer]) -> Result<(), Box<dyn Error>> {
+    let (connection, screen_number) = x11rb::connect(None)?;
+
+    // Some room for the request header
+    let max_bytes = connection.maximum_request_bytes().saturating_sub(64);
+
+    if offers.iter().any(|offer| offer.data.len() > max_bytes) {
+        return Err("Data is too large for a single X11 request".into());
+    }
+
+    let window = create_window(&connection, screen_number)?;
+    let clipboard = intern_atom(&connection, "CLIPBOARD")?;
+    let targets = intern_atom(&connection, "TARGETS")?;
+
+    let atoms = offers
+        .iter()
+        .map(|offer| intern_atom(&connection, &offer.mime_type))
+        .collect::<Result<Vec<Atom>, Box<dyn Error>>>()?;
+
+    connection.set_selection_owner(window, clipboard, CURRENT_TIME)?;
+
+    if connection.get_selection_owner(clipboard)?.reply()?.owner != window {
+        return Err("Failed to take the clipboard".into());
+    }
+
+    connection.flush()?;
+
+    let owner = Owner {
+        connection,
+        window,
+        targets,
+        atoms,
+        offers: offers.to_vec(),
+    };
+
+    // Nobody waits for the result, a failed owner just stops serving like a replaced one
+    thread::spawn(move || {
+        let _ = serve(&owner);
+    });
+
+    Ok(())
+}
+
+fn serve(owner: &Owner) -> Result<(), Box<dyn Error>> {
+    loop {
+        match owner.connection.wait_for_event()? {
+            Event::SelectionRequest(request) => answer_request(owner, &request)?,
+            Event::SelectionClear(clear) if clear.owner == owner.window => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// Writes the requested format to the requestor's property, or refuses with `None` as the
+/// property when it isn't offered
+fn answer_request(owner: &Owner, request: &SelectionRequestEvent) -> Result<(), Box<dyn Error>> {
+    let connection = &owner.connection;
+
+    // Obsolete clients leave the property out and expect the target to be used
+    let property = if request.property == NONE {
+        request.target
+    } else {
+        request.property
+    };
+
+    let offer_index = owner.atoms.iter().position(|atom| *atom == request.target);
+
+    let answered = if request.target == owner.targets {
+        let mut targets = vec![owner.targets];
+        targets.extend(&owner.atoms);
+
+        connection.change_property32(
+            PropMode::REPLACE,
+            request.requestor,
+            property,
+            AtomEnum::ATOM,
+            &targets,
+        )?;
+
+        true
+    } else if let Some(index) = offer_index {
+        connection.change_property8(
+            PropMode::REPLACE,
+            request.requestor,
+            property,
+            request.target,
+            &owner.offers[index].data,
+        )?;
+
+        true
+    } else {
+        false
+    };
+
+    let notify = SelectionNotifyEvent {
+        response_type: SELECTION_NOTIFY_EVENT,
+        sequence: 0,
+        time: request.time,
+        requestor: request.requestor,
+        selection: request.selection,
+        target: request.target,
+        property: if answered { property } else { NONE },
+    };
+
+    connection.send_event(false, request.requestor, EventMask::NO_EVENT, notify)?;
+    connection.flush()?;
+
+    Ok(())
+}
+
+/// Reads the clipboard by asking its owner to write it to a property of a hidden window
+pub struct X11Reader {
+    connection: RustConnection,
+    window: Window,
+    clipboard: Atom,
+    /// Property the owner writes to
+    property: Atom,
+}
+
+impl X11Reader {
+    /// Asks for the clipboard in the target format and returns the property it was written to.
+    /// Other events of the connection are dropped while waiting for the owner.
+    fn convert(&self, target: Atom) -> Result<(Atom, Vec<u8>), Box<dyn Error>> {
+        let connection = &self.connection;
+
+        connection.convert_selection(
+            self.window,
+            self.clipboard,
+            target,
+            self.property,
+            CURRENT_TIME,
+        )?;
+        connection.flush()?;
+
+        let started_at = Instant::now();
+
+        loop {
+            match connection.poll_for_event()? {
+                Some(Event::SelectionNotify(notify)) if notify.requestor == self.window => {
+                    if notify.property == NONE {
+                        return Err("Format is not offered".into());
+                    }
+
+                    break;
+                }
+                Some(_) => {}
+                None if started_at.elapsed() > READ_TIMEOUT => {
+                    return Err("Clipboard owner didn't answer".into());
+                }
+                None => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+
+        let reply = connection
+            .get_property(
+                true,
+                self.window,
+                self.property,
+                AtomEnum::ANY,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()?;
+
+        if reply.type_ == intern_atom(connection, "INCR")? {
+            return Err("Incremental transfers are not supported".into());
+        }
+
+        Ok((reply.type_, reply.value))
+    }
+}
+
+impl ClipboardReader for X11Reader {
+    fn mime_types(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let (_, value) = self.convert(intern_atom(&self.connection, "TARGETS")?)?;
+
+        value
+            .chunks_exact(4)
+            .map(|chunk| -> Result<String, Box<dyn Error>> {
+                let atom = u32::from_ne_bytes(chunk.try_into()?);
+                let name = self.connection.get_atom_name(atom)?.reply()?.name;
+
+                Ok(String::from_utf8_lossy(&name).to_string())
+            })
+            .collect()
+    }
+
+    fn read(&self, mime_type: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let (_, value) = self.convert(intern_atom(&self.connection, mime_type)?)?;
+        Ok(value)
+    }
+}
+
+/// Reader with a connection and hidden window of its own
+pub fn create_reader() -> Result<X11Reader, Box<dyn Error>> {
+    let (connection, screen_number) = x11rb::connect(None)?;
+
+    let window = create_window(&connection, screen_number)?;
+    let clipboard = intern_atom(&connection, "CLIPBOARD")?;
+    let property = intern_atom(&connection, "TUICHER_CLIPBOARD")?;
+
+    Ok(X11Reader {
+        connection,
+        window,
+        clipboard,
+        property,
+    })
+}
+
+/// Gets told by XFixes whenever the CLIPBOARD selection gets a new owner. The clipboard is read
+/// through another connection, so reading doesn't drop the notifications of later copies.
+pub fn watch(on_change: OnChange) -> Result<(), Box<dyn Error>> {
+    let watcher = create_reader()?;
+    let reader = create_reader()?;
+
+    watcher.connection.xfixes_query_version(5, 0)?.reply()?;
+
+    watcher.connection.xfixes_select_selection_input(
+        watcher.window,
+        watcher.clipboard,
+        SelectionEventMask::SET_SELECTION_OWNER,
+    )?;
+    watcher.connection.flush()?;
+
+    thread::spawn(move || loop {
+        match watcher.connection.wait_for_event() {
+            Ok(Event::XfixesSelectionNotify(_)) => on_change(Ok(&reader)),
+            Ok(_) => {}
+            Err(e) => {
+                on_change(Err(format!("Stopped watching the clipboard: {e}").into()));
+                return;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Hidden window used to own or read selections
+fn create_window(
+    connection: &RustConnection,
+    screen_number: usize,
+) -> Result<Window, Box<dyn Error>> {
+    let screen = &connection.setup().roots[screen_number];
+    let window = connection.generate_id()?;
+
+    connection.create_window(
+        COPY_DEPTH_FROM_PARENT,
+        window,
+        screen.root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_OUTPUT,
+        screen.root_visual,
+        &CreateWindowAux::new(),
+    )?;
+
+    Ok(window)
+}
+
+fn intern_atom(connection: &RustConnection, name: &str) -> Result<Atom, Box<dyn Error>> {
+    Ok(connection
+        .intern_atom(false, name.as_bytes())?
+        .reply()?
+        .atom)
+}
@@ -0,0 +1,91 @@
+//! Hyprland request socket. Requests are plain text, `j/` asks for JSON replies.
+//! https://wiki.hyprland.org/IPC/
+
+use std::{
+    env,
+    error::Error,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::compositor::CompositorWindow;
+
+#[derive(Deserialize)]
+struct Client {
+    address: String,
+    title: String,
+    class: String,
+    workspace: Workspace,
+    pid: i64,
+    #[serde(default = "default_mapped")]
+    mapped: bool,
+}
+
+#[derive(Deserialize)]
+struct Workspace {
+    name: String,
+}
+
+fn default_mapped() -> bool {
+    true
+}
+
+/// `$XDG_RUNTIME_DIR/hypr/<signature>`, or `/tmp/hypr/<signature>` on older versions
+pub fn get_socket_path(signature: &str) -> Option<PathBuf> {
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+
+    runtime_dir
+        .into_iter()
+        .chain([PathBuf::from("/tmp")])
+        .map(|dir| dir.join("hypr").join(signature).join(".socket.sock"))
+        .find(|path| path.exists())
+}
+
+pub fn request(socket: &Path, request: &str) -> Result<String, Box<dyn Error>> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.write_all(request.as_bytes())?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+
+    Ok(reply)
+}
+
+pub fn get_windows(socket: &Path) -> Result<Vec<CompositorWindow>, Box<dyn Error>> {
+    let clients: Vec<Client> = serde_json::from_str(&request(socket, "j/clients")?)?;
+
+    let windows = clients
+        .into_iter()
+        .filter(|client| client.mapped)
+        .map(|client| CompositorWindow {
+            id: client.address,
+            title: client.title,
+            class: client.class,
+            workspace: client.workspace.name,
+            pid: u32::try_from(client.pid).ok(),
+        })
+        .collect();
+
+    Ok(windows)
+}
+
+/// Name of the focused workspace
+pub fn get_active_workspace(socket: &Path) -> Result<String, Box<dyn Error>> {
+    let workspace: Workspace = serde_json::from_str(&request(socket, "j/activeworkspace")?)?;
+
+    Ok(workspace.name)
+}
+
+/// Runs a dispatcher, e.g. `focuswindow address:0x1234`
+pub fn dispatch(socket: &Path, dispatcher: &str) -> Result<(), Box<dyn Error>> {
+    let reply = request(socket, &format!("dispatch {dispatcher}"))?;
+
+    if reply.trim() != "ok" {
+        return Err(format!("Hyprland: {}", reply.trim()).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! Open windows from the compositor IPC. Hyprland and sway/i3 are supported.
+
+use std::{env, error::Error, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub mod hyprland;
+pub mod sway;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompositorWindow {
+    /// Address on Hyprland, container id on sway and i3
+    pub id: String,
+    pub title: String,
+    /// Wayland app id or X11 class
+    pub class: String,
+    pub workspace: String,
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Compositor {
+    /// Path of the Hyprland request socket
+    Hyprland(PathBuf),
+    /// Path of the sway or i3 IPC socket
+    Sway(PathBuf),
+}
+
+impl Compositor {
+    /// The compositor of the current session, from the variables it sets
+    pub fn detect() -> Option<Self> {
+        if let Ok(signature) = env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+            return Some(Compositor::Hyprland(hyprland::get_socket_path(&signature)?));
+        }
+
+        env::var_os("SWAYSOCK")
+            .or_else(|| env::var_os("I3SOCK"))
+            .map(|path| Compositor::Sway(PathBuf::from(path)))
+    }
+
+    pub fn get_windows(&self) -> Result<Vec<CompositorWindow>, Box<dyn Error>> {
+        match self {
+            Compositor::Hyprland(socket) => hyprland::get_windows(socket),
+            Compositor::Sway(socket) => sway::get_windows(socket),
+        }
+    }
+
+    pub fn focus_window(&self, id: &str) -> Result<(), Box<dyn Error>> {
+        match self {
+            Compositor::Hyprland(socket) => {
+                hyprland::dispatch(socket, &format!("focuswindow address:{id}"))
+            }
+            Compositor::Sway(socket) => sway::run_command(socket, &format!("[con_id={id}] focus")),
+        }
+    }
+
+    pub fn close_window(&self, id: &str) -> Result<(), Box<dyn Error>> {
+        match self {
+            Compositor::Hyprland(socket) => {
+                hyprland::dispatch(socket, &format!("closewindow address:{id}"))
+            }
+            Compositor::Sway(socket) => sway::run_command(socket, &format!("[con_id={id}] kill")),
+        }
+    }
+
+    /// Moves the window to a workspace, or to the current one when `None`
+    pub fn move_window(&self, id: &str, workspace: Option<&str>) -> Result<(), Box<dyn Error>> {
+        match self {
+            Compositor::Hyprland(socket) => {
+                let workspace = match workspace {
+                    Some(workspace) => workspace.to_string(),
+                    None => hyprland::get_active_workspace(socket)?,
+                };
+
+                // Numbers are workspace ids, anything else a workspace name
+                let workspace = if workspace.parse::<i64>().is_ok() {
+                    workspace
+                } else {
+                    format!("name:{workspace}")
+                };
+
+                hyprland::dispatch(
+                    socket,
+                    &format!("movetoworkspacesilent {workspace},address:{id}"),
+                )
+            }
+            Compositor::Sway(socket) => {
+                let workspace = workspace.unwrap_or("current");
+
+                sway::run_command(
+                    socket,
+                    &format!("[con_id={id}] move container to workspace {workspace}"),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! sway and i3 IPC. Messages are `i3-ipc`, the payload length and the message type as native
+//! endian u32, then the payload.
+//! https://i3wm.org/docs/ipc.html
+
+use std::{
+    error::Error,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+};
+
+use serde_json::Value;
+
+use crate::compositor::CompositorWindow;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const RUN_COMMAND: u32 = 0;
+const GET_TREE: u32 = 4;
+
+pub fn request(socket: &Path, message_type: u32, payload: &str) -> Result<Value, Box<dyn Error>> {
+    let mut stream = UnixStream::connect(socket)?;
+
+    let mut message = MAGIC.to_vec();
+    message.extend((payload.len() as u32).to_ne_bytes());
+    message.extend(message_type.to_ne_bytes());
+    message.extend(payload.as_bytes());
+    stream.write_all(&message)?;
+
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+
+    if &header[..6] != MAGIC {
+        return Err("Invalid IPC reply".into());
+    }
+
+    let length = u32::from_ne_bytes(header[6..10].try_into()?) as usize;
+    let mut reply = vec![0u8; length];
+    stream.read_exact(&mut reply)?;
+
+    Ok(serde_json::from_slice(&reply)?)
+}
+
+pub fn get_windows(socket: &Path) -> Result<Vec<CompositorWindow>, Box<dyn Error>> {
+    let tree = request(socket, GET_TREE, "")?;
+    let mut windows: Vec<CompositorWindow> = vec![];
+
+    collect_windows(&tree, "", &mut windows);
+
+    Ok(windows)
+}
+
+/// Walks the tree keeping the name of the last workspace above each window
+fn collect_windows(node: &Value, workspace: &str, windows: &mut Vec<CompositorWindow>) {
+    let workspace = if node["type"] == "workspace" {
+        node["name"].as_str().unwrap_or(workspace)
+    } else {
+        workspace
+    };
+
+    // Wayland windows have an app_id, X11 windows a class
+    let class = node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str());
+
+    let is_window = matches!(node["type"].as_str(), Some("con" | "floating_con"));
+
+    if let (true, Some(class), Some(id)) = (is_window, class, node["id"].as_u64()) {
+        windows.push(CompositorWindow {
+            id: id.to_string(),
+            title: node["name"].as_str().unwrap_or_default().to_string(),
+            class: class.to_string(),
+            workspace: workspace.to_string(),
+            pid: node["pid"].as_u64().and_then(|pid| u32::try_from(pid).ok()),
+        });
+    }
+
+    for key in ["nodes", "floating_nodes"] {
+        for child in node[key].as_array().into_iter().flatten() {
+            collect_windows(child, workspace, windows);
+        }
+    }
+}
+
+/// Runs a command, e.g. `[con_id=12] focus`, and fails if sway reports an error
+pub fn run_command(socket: &Path, command: &str) -> Result<(), Box<dyn Error>> {
+    let reply = request(socket, RUN_COMMAND, command)?;
+
+    for result in reply.as_array().into_iter().flatten() {
+        if result["success"] != true {
+            let error = result["error"].as_str().unwrap_or("Command failed");
+            return Err(format!("sway: {error}").into());
+        }
+    }
+
+    Ok(())
+}
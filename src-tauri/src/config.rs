@@ -1,6 +1,172 @@
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
 use tuicher_rs::config::{get_config, Config};
 
+/// Settings of the launcher that aren't part of the tuicher-rs config
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LauncherConfig {
+    /// Command used to run apps with `Terminal=true`, e.g. `kitty -e`. The app command line is
+    /// appended to it. When empty the terminal is detected from `$TERMINAL` and the `PATH`.
+    pub terminal: Option<String>,
+    /// Run launched apps in their own `systemd-run --user --scope` unit
+    pub systemd_scope: bool,
+    /// Settings of the search providers by id, e.g. `windows`. Providers that aren't listed
+    /// are enabled with their default keyword.
+    pub providers: HashMap<String, ProviderConfig>,
+    /// Launch profiles offered as secondary actions on app results
+    pub profiles: Vec<LaunchProfile>,
+    /// Name of the profile each app is launched with by default, by desktop file ID. Set from
+    /// the action menu of the app.
+    pub app_profiles: HashMap<String, String>,
+    pub clipboard: ClipboardConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ProviderConfig {
+    pub enabled: bool,
+    /// Replaces the default keyword of the provider
+    pub keyword: Option<String>,
+    /// Most results the provider adds to searches without a keyword
+    pub max_results: Option<usize>,
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keyword: None,
+            max_results: None,
+        }
+    }
+}
+
+/// Limits of the clipboard history
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    /// Entries kept besides the pinned ones, the oldest are dropped first
+    pub max_entries: usize,
+    /// Copies larger than this many bytes aren't kept
+    pub max_entry_size: usize,
+    /// Copies offered in one of these formats aren't kept. Password managers use them to mark
+    /// secrets.
+    pub ignored_mime_types: Vec<String>,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 200,
+            max_entry_size: 10 * 1024 * 1024,
+            ignored_mime_types: vec!["x-kde-passwordManagerHint".to_string()],
+        }
+    }
+}
+
+/// A named way of launching apps, e.g. on the dedicated GPU or with `MANGOHUD=1`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct LaunchProfile {
+    pub name: String,
+    /// Command the app command line is appended to, e.g. `prime-run` or `gamemoderun`
+    pub wrapper: Option<String>,
+    /// Variables set for the app on top of the launcher environment
+    pub env: HashMap<String, String>,
+    /// Run the app in a terminal even if its desktop entry doesn't ask for one
+    pub terminal: bool,
+    /// Run the app as root through `pkexec`
+    pub pkexec: bool,
+}
+
+impl LauncherConfig {
+    pub fn get_profile(&self, name: &str) -> Option<&LaunchProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// The profile the app with the given desktop file ID is launched with by default. Profiles
+    /// that run as root are never defaults, even when the config names one.
+    pub fn get_app_profile(&self, id: &str) -> Option<&LaunchProfile> {
+        self.get_profile(self.app_profiles.get(id)?)
+            .filter(|profile| !profile.pkexec)
+    }
+}
+
+impl Default for LauncherConfig {
+    fn default() -> Self {
+        Self {
+            terminal: None,
+            systemd_scope: false,
+            providers: HashMap::new(),
+            profiles: vec![
+                LaunchProfile {
+                    name: "Run in Terminal".to_string(),
+                    terminal: true,
+                    ..Default::default()
+                },
+                LaunchProfile {
+                    name: "Run as Root".to_string(),
+                    pkexec: true,
+                    ..Default::default()
+                },
+                LaunchProfile {
+                    name: "Run on Dedicated GPU".to_string(),
+                    env: HashMap::from([("DRI_PRIME".to_string(), "1".to_string())]),
+                    ..Default::default()
+                },
+            ],
+            app_profiles: HashMap::new(),
+            clipboard: ClipboardConfig::default(),
+        }
+    }
+}
+
+pub fn get_launcher_config() -> Result<LauncherConfig, Box<dyn Error>> {
+    let path = get_launcher_config_path()?;
+
+    if !path.exists() {
+        return Ok(LauncherConfig::default());
+    }
+
+    let content = fs::read_to_string(path)?;
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn write_launcher_config(config: &LauncherConfig) -> Result<(), Box<dyn Error>> {
+    let content = serde_json::to_string_pretty(config)?;
+    fs::write(get_launcher_config_path()?, content)?;
+
+    Ok(())
+}
+
+fn get_launcher_config_path() -> Result<PathBuf, Box<dyn Error>> {
+    let mut path = dirs::config_dir()
+        .ok_or_else(|| "Failed to get config dir")?
+        .join("tuicher");
+
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+
+    path.push("launcher.json");
+
+    Ok(path)
+}
+
 #[tauri::command]
 pub fn invoke_get_config() -> Result<Config, String> {
     Ok(get_config().map_err(|_| "Failed to get config")?)
 }
+
+#[tauri::command]
+pub fn invoke_get_launcher_config() -> Result<LauncherConfig, String> {
+    get_launcher_config().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn invoke_write_launcher_config(config: LauncherConfig) -> Result<(), String> {
+    write_launcher_config(&config).map_err(|e| e.to_string())
+}
@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bincode::{config, Decode, Encode};
+use tauri::State;
+
+use crate::{state::AppState, utils::write_private_file};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+#[derive(Encode, Decode, Debug, Clone, Default)]
+pub struct LaunchHistory {
+    pub entries: HashMap<String, LaunchEntry>,
+}
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct LaunchEntry {
+    pub count: u32,
+    /// Unix timestamp in seconds of the last launch
+    pub last_used: u64,
+}
+
+impl LaunchHistory {
+    /// Frecency of the app at `path`. Launch count weighted by how recently it was used.
+    pub fn frecency(&self, path: &Path, now: u64) -> f64 {
+        let Some(entry) = self.entries.get(&path.display().to_string()) else {
+            return 0.0;
+        };
+
+        let age = now.saturating_sub(entry.last_used);
+
+        let weight = if age < 4 * HOUR {
+            100.0
+        } else if age < DAY {
+            80.0
+        } else if age < 3 * DAY {
+            60.0
+        } else if age < 7 * DAY {
+            40.0
+        } else if age < 30 * DAY {
+            20.0
+        } else if age < 90 * DAY {
+            10.0
+        } else {
+            5.0
+        };
+
+        entry.count as f64 * weight
+    }
+}
+
+pub fn get_launch_history() -> Result<LaunchHistory, Box<dyn Error>> {
+    let path = get_launch_history_path()?;
+
+    if !path.exists() {
+        return Ok(LaunchHistory::default());
+    }
+
+    let bytes = fs::read(path)?;
+    let (history, _): (LaunchHistory, usize) =
+        bincode::decode_from_slice(&bytes, config::standard())?;
+
+    Ok(history)
+}
+
+fn write_launch_history(history: &LaunchHistory) -> Result<(), Box<dyn Error>> {
+    let bytes = bincode::encode_to_vec(history, config::standard())?;
+    write_private_file(&get_launch_history_path()?, &bytes)
+}
+
+/// Counts a launch of the app at `path` and returns the updated history. A history that can't
+/// be read is left as it is instead of being replaced.
+pub fn record_app_launch(path: &Path) -> Result<LaunchHistory, Box<dyn Error>> {
+    let mut history = get_launch_history()?;
+
+    let entry = history
+        .entries
+        .entry(path.display().to_string())
+        .or_insert(LaunchEntry {
+            count: 0,
+            last_used: 0,
+        });
+
+    entry.count = entry.count.saturating_add(1);
+    entry.last_used = now();
+
+    write_launch_history(&history)?;
+
+    Ok(history)
+}
+
+/// Removes entries not used in the last `max_age_days` and entries of apps that were uninstalled
+pub fn prune_launch_history(max_age_days: u64) -> Result<usize, Box<dyn Error>> {
+    let mut history = get_launch_history()?;
+    let now = now();
+    let previous_len = history.entries.len();
+
+    history.entries.retain(|path, entry| {
+        now.saturating_sub(entry.last_used) <= max_age_days * DAY && Path::new(path).exists()
+    });
+
+    write_launch_history(&history)?;
+
+    Ok(previous_len - history.entries.len())
+}
+
+pub fn reset_launch_history() -> Result<(), Box<dyn Error>> {
+    let path = get_launch_history_path()?;
+
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn get_launch_history_path() -> Result<PathBuf, Box<dyn Error>> {
+    let mut path = dirs::data_dir()
+        .ok_or_else(|| "Failed to get data dir")?
+        .join("tuicher");
+
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+
+    path.push("history.bin");
+
+    Ok(path)
+}
+
+#[tauri::command]
+pub fn invoke_reset_app_history(state: State<AppState>) -> Result<(), String> {
+    reset_launch_history().map_err(|e| e.to_string())?;
+    state.set_history(LaunchHistory::default());
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn invoke_prune_app_history(
+    max_age_days: u64,
+    state: State<AppState>,
+) -> Result<usize, String> {
+    let removed = prune_launch_history(max_age_days).map_err(|e| e.to_string())?;
+    state.set_history(get_launch_history().map_err(|e| e.to_string())?);
+
+    Ok(removed)
+}
@@ -0,0 +1,454 @@
+//! Parsing of the desktop entry `Exec` key as described in
+//! https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html
+
+use std::{error::Error, path::Path};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Text(String),
+    FieldCode(char),
+}
+
+/// A single argument of the command line. Unquoted arguments can mix text and field codes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecArg {
+    pub segments: Vec<Segment>,
+}
+
+impl ExecArg {
+    fn field_code(&self) -> Option<char> {
+        match self.segments.as_slice() {
+            [Segment::FieldCode(code)] => Some(*code),
+            _ => None,
+        }
+    }
+
+    fn has_field_code(&self, codes: &[char]) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::FieldCode(code) if codes.contains(code)))
+    }
+}
+
+/// Values the field codes get replaced with
+#[derive(Debug, Clone, Default)]
+pub struct ExecContext<'a> {
+    /// Translated name of the app, used by `%c`
+    pub name: Option<&'a str>,
+    /// Value of the `Icon` key, used by `%i`
+    pub icon: Option<&'a str>,
+    /// Location of the desktop file, used by `%k`
+    pub desktop_file: Option<&'a Path>,
+    /// Files or urls the app is opened with, used by `%f %F %u %U`
+    pub targets: &'a [String],
+}
+
+pub fn parse_exec(exec: &str) -> Result<Vec<ExecArg>, Box<dyn Error>> {
+    let exec = unescape_string(exec);
+    let mut chars = exec.chars();
+    let mut args: Vec<ExecArg> = vec![];
+    let mut current: Option<Vec<Segment>> = None;
+
+    while let Some(char) = chars.next() {
+        match char {
+            ' ' | '\t' | '\n' => {
+                if let Some(segments) = current.take() {
+                    args.push(ExecArg { segments });
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '`' | '$' | '\\')) => text.push(escaped),
+                            Some(other) => {
+                                text.push('\\');
+                                text.push(other);
+                            }
+                            None => return Err("Unterminated escape in Exec key".into()),
+                        },
+                        Some(other) => text.push(other),
+                        None => return Err("Unterminated quote in Exec key".into()),
+                    }
+                }
+
+                push_text(current.get_or_insert_with(Vec::new), &text);
+            }
+            '%' => {
+                let segments = current.get_or_insert_with(Vec::new);
+
+                match chars.next() {
+                    Some('%') => push_text(segments, "%"),
+                    Some(code) => segments.push(Segment::FieldCode(code)),
+                    None => return Err("Incomplete field code in Exec key".into()),
+                }
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    push_text(current.get_or_insert_with(Vec::new), &escaped.to_string());
+                }
+            }
+            other => push_text(current.get_or_insert_with(Vec::new), &other.to_string()),
+        }
+    }
+
+    if let Some(segments) = current.take() {
+        args.push(ExecArg { segments });
+    }
+
+    if args.is_empty() {
+        return Err("Exec key is empty".into());
+    }
+
+    Ok(args)
+}
+
+/// Kind of targets a command line takes through its field codes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetKind {
+    /// `%f` or `%F`, local paths only
+    Files,
+    /// `%u` or `%U`, urls or local paths
+    Urls,
+}
+
+pub fn get_target_kind(args: &[ExecArg]) -> Option<TargetKind> {
+    if args.iter().any(|arg| arg.has_field_code(&['u', 'U'])) {
+        Some(TargetKind::Urls)
+    } else if args.iter().any(|arg| arg.has_field_code(&['f', 'F'])) {
+        Some(TargetKind::Files)
+    } else {
+        None
+    }
+}
+
+/// Replaces the field codes and returns the command lines to run. Apps that take a single
+/// file or url (`%f` or `%u`) are run once per target.
+pub fn expand_exec(args: &[ExecArg], context: &ExecContext) -> Vec<Vec<String>> {
+    let single_target = args.iter().any(|arg| arg.has_field_code(&['f', 'u']));
+
+    if single_target && context.targets.len() > 1 {
+        return context
+            .targets
+            .iter()
+            .map(|target| {
+                let targets = [target.to_owned()];
+                let context = ExecContext {
+                    targets: &targets,
+                    ..context.clone()
+                };
+
+                expand_command(args, &context)
+            })
+            .collect();
+    }
+
+    vec![expand_command(args, context)]
+}
+
+fn expand_command(args: &[ExecArg], context: &ExecContext) -> Vec<String> {
+    let mut command: Vec<String> = vec![];
+
+    for arg in args {
+        match arg.field_code() {
+            Some('F') => command.extend(context.targets.iter().map(|target| to_file(target))),
+            Some('U') => command.extend(context.targets.iter().cloned()),
+            Some('f') => command.extend(context.targets.first().map(|target| to_file(target))),
+            Some('u') => command.extend(context.targets.first().cloned()),
+            Some('i') => {
+                if let Some(icon) = context.icon {
+                    command.push("--icon".to_string());
+                    command.push(icon.to_string());
+                }
+            }
+            Some('c') => command.extend(context.name.map(|name| name.to_string())),
+            Some('k') => {
+                command.extend(context.desktop_file.map(|path| path.display().to_string()))
+            }
+            // Deprecated and unknown field codes are removed
+            Some(_) => {}
+            None => command.push(expand_segments(&arg.segments, context)),
+        }
+    }
+
+    command
+}
+
+fn expand_segments(segments: &[Segment], context: &ExecContext) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_owned(),
+            Segment::FieldCode('f') | Segment::FieldCode('F') => context
+                .targets
+                .first()
+                .map(|target| to_file(target))
+                .unwrap_or_default(),
+            Segment::FieldCode('u') | Segment::FieldCode('U') => {
+                context.targets.first().cloned().unwrap_or_default()
+            }
+            Segment::FieldCode('c') => context.name.unwrap_or_default().to_string(),
+            Segment::FieldCode('k') => context
+                .desktop_file
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            Segment::FieldCode(_) => String::new(),
+        })
+        .collect()
+}
+
+fn push_text(segments: &mut Vec<Segment>, text: &str) {
+    if let Some(Segment::Text(last)) = segments.last_mut() {
+        last.push_str(text);
+    } else {
+        segments.push(Segment::Text(text.to_string()));
+    }
+}
+
+/// `%f` and `%F` expect local paths. Only `file://` urls are decoded, a path can contain `%`.
+fn to_file(target: &str) -> String {
+    match target.strip_prefix("file://") {
+        Some(path) => percent_decode(path.strip_prefix("localhost").unwrap_or(path)),
+        None => target.to_string(),
+    }
+}
+
+/// Replaces `%XX` escapes with their byte, keeping invalid ones as they are
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Escape sequences of the `string` value type, applied before the quoting rules
+fn unescape_string(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(exec: &str, context: &ExecContext) -> Vec<Vec<String>> {
+        expand_exec(&parse_exec(exec).unwrap(), context)
+    }
+
+    fn with_targets(targets: &[String]) -> ExecContext<'_> {
+        ExecContext {
+            targets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_unquoted_args() {
+        assert_eq!(
+            expand(
+                "firefox  --new-window\tabout:blank",
+                &ExecContext::default()
+            ),
+            vec![vec!["firefox", "--new-window", "about:blank"]]
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_args_together() {
+        assert_eq!(
+            expand(r#""/opt/My App/app" --name="a b""#, &ExecContext::default()),
+            vec![vec!["/opt/My App/app", "--name=a b"]]
+        );
+    }
+
+    #[test]
+    fn unescapes_inside_quotes() {
+        // `\\` in the file is one backslash after the string unescaping
+        assert_eq!(
+            expand(
+                r#"sh -c "echo \\"hi\\" \\$HOME \\\\""#,
+                &ExecContext::default()
+            ),
+            vec![vec!["sh", "-c", r#"echo "hi" $HOME \"#]]
+        );
+    }
+
+    #[test]
+    fn unescapes_string_value_before_quoting() {
+        // `\s` is a space before the quoting rules apply, so it splits unless quoted
+        assert_eq!(
+            expand(r#"app\s--flag "a\sb""#, &ExecContext::default()),
+            vec![vec!["app", "--flag", "a b"]]
+        );
+    }
+
+    #[test]
+    fn fails_on_unterminated_quote() {
+        assert!(parse_exec(r#"app "unterminated"#).is_err());
+    }
+
+    #[test]
+    fn fails_on_empty_exec() {
+        assert!(parse_exec("   ").is_err());
+    }
+
+    #[test]
+    fn expands_percent_sign() {
+        assert_eq!(
+            expand("printf 100%%", &ExecContext::default()),
+            vec![vec!["printf", "100%"]]
+        );
+    }
+
+    #[test]
+    fn removes_field_codes_without_values() {
+        assert_eq!(
+            expand("app %f %U %i %c %k %d", &ExecContext::default()),
+            vec![vec!["app"]]
+        );
+    }
+
+    #[test]
+    fn expands_file_lists() {
+        let targets = vec!["file:///tmp/a.txt".to_string(), "/tmp/b.txt".to_string()];
+
+        assert_eq!(
+            expand("editor %F", &with_targets(&targets)),
+            vec![vec!["editor", "/tmp/a.txt", "/tmp/b.txt"]]
+        );
+    }
+
+    #[test]
+    fn decodes_file_urls() {
+        let targets = vec![
+            "file:///home/u/My%20Doc.pdf".to_string(),
+            "file:///tmp/%C3%A9t%C3%A9%2".to_string(),
+            "/tmp/100%20.txt".to_string(),
+        ];
+
+        assert_eq!(
+            expand("viewer %F", &with_targets(&targets)),
+            vec![vec![
+                "viewer",
+                "/home/u/My Doc.pdf",
+                "/tmp/été%2",
+                "/tmp/100%20.txt"
+            ]]
+        );
+    }
+
+    #[test]
+    fn expands_url_lists() {
+        let targets = vec![
+            "https://example.com".to_string(),
+            "file:///tmp/a.txt".to_string(),
+        ];
+
+        assert_eq!(
+            expand("browser %U", &with_targets(&targets)),
+            vec![vec!["browser", "https://example.com", "file:///tmp/a.txt"]]
+        );
+    }
+
+    #[test]
+    fn runs_single_target_apps_once_per_target() {
+        let targets = vec!["/tmp/a.txt".to_string(), "/tmp/b.txt".to_string()];
+
+        assert_eq!(
+            expand("viewer %f", &with_targets(&targets)),
+            vec![vec!["viewer", "/tmp/a.txt"], vec!["viewer", "/tmp/b.txt"]]
+        );
+
+        assert_eq!(
+            expand("browser %u", &with_targets(&targets)),
+            vec![vec!["browser", "/tmp/a.txt"], vec!["browser", "/tmp/b.txt"]]
+        );
+    }
+
+    #[test]
+    fn expands_field_codes_inside_args() {
+        let targets = vec!["https://example.com".to_string()];
+
+        assert_eq!(
+            expand("browser --url=%u", &with_targets(&targets)),
+            vec![vec!["browser", "--url=https://example.com"]]
+        );
+    }
+
+    #[test]
+    fn expands_icon_name_and_location() {
+        let context = ExecContext {
+            name: Some("My App"),
+            icon: Some("my-app"),
+            desktop_file: Some(Path::new("/usr/share/applications/my-app.desktop")),
+            targets: &[],
+        };
+
+        assert_eq!(
+            expand("app %i --class %c %k", &context),
+            vec![vec![
+                "app",
+                "--icon",
+                "my-app",
+                "--class",
+                "My App",
+                "/usr/share/applications/my-app.desktop"
+            ]]
+        );
+    }
+
+    #[test]
+    fn finds_target_kind() {
+        let kind = |exec: &str| get_target_kind(&parse_exec(exec).unwrap());
+
+        assert_eq!(kind("app %F"), Some(TargetKind::Files));
+        assert_eq!(kind("app %u"), Some(TargetKind::Urls));
+        assert_eq!(kind("app --open=%f"), Some(TargetKind::Files));
+        assert_eq!(kind("app %i"), None);
+    }
+}
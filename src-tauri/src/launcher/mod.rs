@@ -0,0 +1,192 @@
+use std::{
+    env,
+    error::Error,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use freedesktop_desktop_entry::{get_languages_from_env, DesktopEntry};
+
+use crate::{
+    config::{LaunchProfile, LauncherConfig},
+    launcher::{
+        exec::{expand_exec, parse_exec, ExecContext},
+        process::spawn_detached,
+    },
+};
+
+pub mod exec;
+pub mod process;
+
+/// Terminals tried when none is configured, with the flag that runs a command in them
+const TERMINALS: [(&str, &[&str]); 8] = [
+    ("kitty", &["-e"]),
+    ("alacritty", &["-e"]),
+    ("foot", &[]),
+    ("wezterm", &["start", "--"]),
+    ("gnome-terminal", &["--"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("xterm", &["-e"]),
+];
+
+/// Variables an app run through `pkexec` needs to reach the user's session
+const PKEXEC_VARIABLES: [&str; 4] = [
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XDG_RUNTIME_DIR",
+    "XAUTHORITY",
+];
+
+/// Launches the desktop entry at `path`, or one of its actions, with the given files or urls.
+/// The profile can wrap the command, add variables or run it in a terminal or as root.
+pub fn launch_app(
+    path: &Path,
+    action: Option<&str>,
+    targets: &[String],
+    profile: Option<&LaunchProfile>,
+    launcher_config: &LauncherConfig,
+) -> Result<(), Box<dyn Error>> {
+    let locales = get_languages_from_env();
+    let entry = DesktopEntry::from_path(path, Some(&locales))?;
+
+    let exec = match action {
+        Some(action) => entry
+            .action_exec(action)
+            .ok_or_else(|| format!("Action {action} has no Exec key"))?,
+        None => entry
+            .exec()
+            .ok_or_else(|| "Desktop entry has no Exec key")?,
+    };
+
+    let name = entry.name(&locales).map(|name| name.to_string());
+
+    let context = ExecContext {
+        name: name.as_deref(),
+        icon: entry.icon(),
+        desktop_file: Some(path),
+        targets,
+    };
+
+    let working_dir = entry
+        .desktop_entry("Path")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir());
+
+    let unit_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let wrapper = match profile.and_then(|profile| profile.wrapper.as_ref()) {
+        Some(wrapper) if !wrapper.trim().is_empty() => {
+            expand_exec(&parse_exec(wrapper)?, &ExecContext::default()).concat()
+        }
+        _ => vec![],
+    };
+
+    let in_terminal = entry.terminal() || profile.is_some_and(|profile| profile.terminal);
+
+    for mut command_line in expand_exec(&parse_exec(exec)?, &context) {
+        command_line = [wrapper.clone(), command_line].concat();
+
+        if let Some(profile) = profile.filter(|profile| profile.pkexec) {
+            command_line = [get_pkexec_command(profile), command_line].concat();
+        }
+
+        if in_terminal {
+            command_line = [get_terminal_command(launcher_config)?, command_line].concat();
+        }
+
+        let (program, args) = command_line
+            .split_first()
+            .ok_or_else(|| "Exec key is empty")?;
+
+        let mut command = Command::new(program);
+        command.args(args);
+
+        if let Some(working_dir) = &working_dir {
+            command.current_dir(working_dir);
+        }
+
+        if let Some(profile) = profile {
+            command.envs(&profile.env);
+        }
+
+        spawn_detached(command, &unit_name, launcher_config)
+            .map_err(|e| format!("Failed to launch {program}: {e}"))?;
+    }
+
+    Ok(())
+}
+
+/// `pkexec` clears the environment, so the session variables and the profile variables are
+/// passed again through `env`
+fn get_pkexec_command(profile: &LaunchProfile) -> Vec<String> {
+    let session_variables = PKEXEC_VARIABLES
+        .iter()
+        .filter_map(|name| Some(format!("{name}={}", env::var(name).ok()?)));
+
+    let profile_variables = profile
+        .env
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"));
+
+    ["pkexec".to_string(), "env".to_string()]
+        .into_iter()
+        .chain(session_variables)
+        .chain(profile_variables)
+        .collect()
+}
+
+fn get_terminal_command(launcher_config: &LauncherConfig) -> Result<Vec<String>, Box<dyn Error>> {
+    if let Some(terminal) = &launcher_config.terminal {
+        if !terminal.trim().is_empty() {
+            return Ok(expand_exec(&parse_exec(terminal)?, &ExecContext::default()).concat());
+        }
+    }
+
+    if let Ok(terminal) = env::var("TERMINAL") {
+        if let Some((_, args)) = TERMINALS.iter().find(|(name, _)| *name == terminal) {
+            return Ok([vec![terminal.clone()], to_strings(args)].concat());
+        }
+
+        if find_executable(&terminal).is_some() {
+            return Ok(vec![terminal, "-e".to_string()]);
+        }
+    }
+
+    TERMINALS
+        .iter()
+        .find(|(name, _)| find_executable(name).is_some())
+        .map(|(name, args)| [vec![name.to_string()], to_strings(args)].concat())
+        .ok_or_else(|| "No terminal emulator found. Set one in the launcher config".into())
+}
+
+/// Looks up a program the same way the shell does. Absolute paths are returned if they are
+/// executable.
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+
+    if path.is_absolute() {
+        return if is_executable(path) {
+            Some(path.to_path_buf())
+        } else {
+            None
+        };
+    }
+
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
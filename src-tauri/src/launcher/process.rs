@@ -0,0 +1,122 @@
+use std::{
+    env,
+    error::Error,
+    ffi::OsString,
+    io,
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    sync::OnceLock,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{config::LauncherConfig, launcher::find_executable};
+
+/// Set by tuicher on its own process for the webview, launched apps get the original value
+const COMPOSITING_MODE_VARIABLE: &str = "WEBKIT_DISABLE_COMPOSITING_MODE";
+
+/// Startup notification and activation tokens are used once, by the app they were made for.
+/// The ones tuicher was started with belong to tuicher, so they are never passed on.
+const STARTUP_VARIABLES: [&str; 2] = ["DESKTOP_STARTUP_ID", "XDG_ACTIVATION_TOKEN"];
+
+static ORIGINAL_COMPOSITING_MODE: OnceLock<Option<OsString>> = OnceLock::new();
+
+/// Remembers the value [`COMPOSITING_MODE_VARIABLE`] had when tuicher started. Must be called
+/// before tuicher changes its own environment.
+pub fn save_original_environment() {
+    let _ = ORIGINAL_COMPOSITING_MODE.set(env::var_os(COMPOSITING_MODE_VARIABLE));
+}
+
+/// Spawns a command that outlives tuicher. The child gets a clean environment, its own session
+/// and no stdio from tuicher, and its own systemd scope when the launcher config asks for it. A
+/// thread waits on it so it doesn't become a zombie.
+pub fn spawn_detached(
+    command: Command,
+    name: &str,
+    launcher_config: &LauncherConfig,
+) -> Result<(), Box<dyn Error>> {
+    let mut command = if launcher_config.systemd_scope && find_executable("systemd-run").is_some() {
+        wrap_in_scope(command, name)
+    } else {
+        command
+    };
+
+    clean_environment(&mut command);
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // SAFETY: setsid is async-signal-safe and doesn't allocate
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(())
+        });
+    }
+
+    let mut child = command.spawn()?;
+
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    Ok(())
+}
+
+fn clean_environment(command: &mut Command) {
+    match ORIGINAL_COMPOSITING_MODE.get().cloned().flatten() {
+        Some(value) => command.env(COMPOSITING_MODE_VARIABLE, value),
+        None => command.env_remove(COMPOSITING_MODE_VARIABLE),
+    };
+
+    for name in STARTUP_VARIABLES {
+        command.env_remove(name);
+    }
+}
+
+/// Runs the command through `systemd-run` so it gets its own `app-tuicher-<name>-<id>.scope`
+/// unit and isn't part of tuicher's cgroup
+fn wrap_in_scope(command: Command, name: &str) -> Command {
+    let name: String = name
+        .chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() || char == '_' || char == '.' {
+                char
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+
+    let mut scope = Command::new("systemd-run");
+
+    scope
+        .args(["--user", "--scope", "--quiet", "--collect"])
+        .arg(format!("--unit=app-tuicher-{name}-{id}.scope"))
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args());
+
+    if let Some(dir) = command.get_current_dir() {
+        scope.current_dir(dir);
+    }
+
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => scope.env(key, value),
+            None => scope.env_remove(key),
+        };
+    }
+
+    scope
+}
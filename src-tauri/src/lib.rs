@@ -1,32 +1,62 @@
 use std::error::Error;
 
 use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
-use tuicher_rs::config::get_config;
 
 use crate::{
-    action::invoke_result_action, apps::setup_apps_indexing, config::invoke_get_config,
-    listener::setup_keyboard_listener, search::invoke_search,
+    action::invoke_result_action,
+    apps::setup_apps_indexing,
+    config::{invoke_get_config, invoke_get_launcher_config, invoke_write_launcher_config},
+    history::{invoke_prune_app_history, invoke_reset_app_history},
+    launcher::process::save_original_environment,
+    listener::setup_keyboard_listener,
+    plugins::clipboard::setup_clipboard_watcher,
+    search::{invoke_navigate_back, invoke_reset_search, invoke_search},
+    secondary_actions::invoke_get_result_actions,
+    state::{setup_config_watcher, AppState},
 };
 
 pub mod action;
 pub mod apps;
+pub mod clipboard;
+pub mod compositor;
 pub mod config;
+pub mod history;
+pub mod launcher;
 pub mod listener;
+pub mod matcher;
+pub mod mime;
 pub mod plugins;
+pub mod result;
 pub mod search;
+pub mod secondary_actions;
+pub mod state;
+pub mod typing;
+pub mod utils;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() -> Result<(), Box<dyn Error>> {
+    save_original_environment();
+
     // Fix crashing when using webview on wayland/nvidia
     std::env::set_var("WEBKIT_DISABLE_COMPOSITING_MODE", "1");
     // std::env::set_var("GDK_BACKEND", "x11");
 
+    let state = AppState::load()?;
+
     tauri::Builder::default()
+        .manage(state)
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             invoke_get_config,
+            invoke_get_launcher_config,
+            invoke_write_launcher_config,
             invoke_search,
-            invoke_result_action
+            invoke_navigate_back,
+            invoke_reset_search,
+            invoke_result_action,
+            invoke_get_result_actions,
+            invoke_reset_app_history,
+            invoke_prune_app_history
         ])
         .setup(|app| {
             let app_clone = app.app_handle().to_owned();
@@ -34,7 +64,9 @@ pub fn run() -> Result<(), Box<dyn Error>> {
 
             setup_keyboard_listener(app_thread_clone);
 
-            setup_apps_indexing()?;
+            setup_apps_indexing(app_clone.clone())?;
+            setup_config_watcher(app_clone.clone())?;
+            setup_clipboard_watcher(app_clone.clone());
 
             let window = app_clone
                 .get_webview_window("main")
@@ -42,7 +74,7 @@ pub fn run() -> Result<(), Box<dyn Error>> {
 
             window.close().unwrap();
 
-            let config = get_config().expect("Failed to get config");
+            let config = app_clone.state::<AppState>().config();
 
             WebviewWindowBuilder::new(&app_clone, "tuicher", WebviewUrl::App("index.html".into()))
                 .title("tuicher")
@@ -61,6 +93,9 @@ pub fn run() -> Result<(), Box<dyn Error>> {
                 if window.label() == "tuicher" {
                     api.prevent_close();
                     window.hide().expect("Failed to hide window");
+
+                    // Opening the window again starts from the top level
+                    window.state::<AppState>().clear_navigation();
                 }
             }
             _ => {}
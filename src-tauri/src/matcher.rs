@@ -0,0 +1,430 @@
+/// How well a query matches a text, on the same 0 to 100 scale for every provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub score: f64,
+    /// Matched characters of the text as `[start, end)` char indexes, in order
+    pub ranges: Vec<(usize, usize)>,
+}
+
+impl Match {
+    /// Moves the ranges, for texts that are shown after a prefix
+    pub fn offset(mut self, offset: usize) -> Self {
+        for range in &mut self.ranges {
+            range.0 += offset;
+            range.1 += offset;
+        }
+
+        self
+    }
+}
+
+/// Matches the query against the text, ignoring case. The whole query is tried first, then each
+/// of its words on its own, so `code visual` still finds `Visual Studio Code`. An empty query
+/// matches everything with a score of 0.
+pub fn fuzzy_match(text: &str, query: &str) -> Option<Match> {
+    let query = query.trim().to_lowercase();
+    let words: Vec<Vec<char>> = query
+        .split_whitespace()
+        .map(|word| word.chars().collect())
+        .collect();
+
+    if words.is_empty() {
+        return Some(Match {
+            score: 0.0,
+            ranges: vec![],
+        });
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| to_lower(*c)).collect();
+    let query: Vec<char> = query.chars().collect();
+
+    let whole = match_word(&chars, &lower, &query);
+
+    if words.len() == 1 {
+        return whole;
+    }
+
+    // Words found apart rank below the same words found together
+    let apart = words
+        .iter()
+        .map(|word| match_word(&chars, &lower, word))
+        .collect::<Option<Vec<Match>>>()
+        .map(|found| Match {
+            score: found.iter().map(|found| found.score).sum::<f64>() / words.len() as f64 * 0.9,
+            ranges: merge_ranges(found.into_iter().flat_map(|found| found.ranges).collect()),
+        });
+
+    match (whole, apart) {
+        (Some(whole), Some(apart)) if apart.score > whole.score => Some(apart),
+        (Some(whole), _) => Some(whole),
+        (None, apart) => apart,
+    }
+}
+
+fn match_word(chars: &[char], lower: &[char], query: &[char]) -> Option<Match> {
+    if query.is_empty() || query.len() > lower.len() + 2 {
+        return None;
+    }
+
+    if lower == query {
+        return Some(Match {
+            score: 100.0,
+            ranges: vec![(0, lower.len())],
+        });
+    }
+
+    match_substring(chars, lower, query)
+        .or_else(|| match_acronym(chars, lower, query))
+        .or_else(|| match_subsequence(chars, lower, query))
+        .or_else(|| match_typo(chars, lower, query))
+}
+
+/// The query as a whole inside the text, best at the start, then at the start of a word
+fn match_substring(chars: &[char], lower: &[char], query: &[char]) -> Option<Match> {
+    if query.len() > lower.len() {
+        return None;
+    }
+
+    let coverage = query.len() as f64 / lower.len() as f64;
+
+    (0..=lower.len() - query.len())
+        .filter(|start| &lower[*start..*start + query.len()] == query)
+        .map(|start| {
+            let position_score = if start == 0 {
+                80.0
+            } else if is_word_start(chars, start) {
+                65.0
+            } else {
+                45.0
+            };
+
+            Match {
+                score: position_score + coverage * 15.0,
+                ranges: vec![(start, start + query.len())],
+            }
+        })
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+/// The query as the first letters of the words, e.g. `vsc` for `Visual Studio Code` or `gg`
+/// for `GitGui`
+fn match_acronym(chars: &[char], lower: &[char], query: &[char]) -> Option<Match> {
+    if query.len() < 2 {
+        return None;
+    }
+
+    let word_starts: Vec<usize> = (0..chars.len())
+        .filter(|index| is_word_start(chars, *index) && chars[*index].is_alphanumeric())
+        .collect();
+
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    let mut starts = word_starts.iter();
+
+    for query_char in query {
+        let start = starts.find(|start| lower[**start] == *query_char)?;
+        ranges.push((*start, start + 1));
+    }
+
+    let coverage = query.len() as f64 / word_starts.len() as f64;
+
+    Some(Match {
+        score: 50.0 + coverage * 15.0,
+        ranges,
+    })
+}
+
+/// The query's characters in order with gaps, preferring word starts and runs of characters
+fn match_subsequence(chars: &[char], lower: &[char], query: &[char]) -> Option<Match> {
+    const WORD_START_BONUS: f64 = 3.0;
+    const CONSECUTIVE_BONUS: f64 = 2.0;
+    const BEST_CHAR_SCORE: f64 = 1.0 + WORD_START_BONUS + CONSECUTIVE_BONUS;
+
+    // Cheap check first, most texts don't contain the query's characters at all
+    let mut remaining = lower.iter();
+
+    if !query
+        .iter()
+        .all(|query_char| remaining.any(|c| c == query_char))
+    {
+        return None;
+    }
+
+    // Each row has, for every text char, the best score with the query char there and where
+    // the previous query char was
+    let mut rows: Vec<Vec<Option<(f64, usize)>>> = vec![];
+
+    for query_char in query {
+        let mut row: Vec<Option<(f64, usize)>> = vec![None; lower.len()];
+
+        for (i, text_char) in lower.iter().enumerate() {
+            if text_char != query_char {
+                continue;
+            }
+
+            let char_score = if is_word_start(chars, i) {
+                1.0 + WORD_START_BONUS
+            } else {
+                1.0
+            };
+
+            row[i] = match rows.last() {
+                None => Some((char_score, 0)),
+                Some(previous_row) => previous_row[..i]
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, cell)| {
+                        let (score, _) = (*cell)?;
+                        let bonus = if k + 1 == i { CONSECUTIVE_BONUS } else { 0.0 };
+
+                        Some((score + char_score + bonus, k))
+                    })
+                    .max_by(|a, b| a.0.total_cmp(&b.0)),
+            };
+        }
+
+        rows.push(row);
+    }
+
+    let (mut index, score) = rows
+        .last()?
+        .iter()
+        .enumerate()
+        .filter_map(|(index, cell)| cell.map(|(score, _)| (index, score)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let mut indexes = vec![index];
+
+    for row in rows.iter().skip(1).rev() {
+        index = row[index]?.1;
+        indexes.push(index);
+    }
+
+    indexes.reverse();
+
+    Some(Match {
+        score: 20.0 + 20.0 * score / (BEST_CHAR_SCORE * query.len() as f64),
+        ranges: merge_ranges(
+            indexes
+                .into_iter()
+                .map(|index| (index, index + 1))
+                .collect(),
+        ),
+    })
+}
+
+/// A word start that is a small typo away from the query, e.g. `fierfox`. Short queries are
+/// left out since almost anything is one typo away from them.
+fn match_typo(chars: &[char], lower: &[char], query: &[char]) -> Option<Match> {
+    if query.len() < 4 {
+        return None;
+    }
+
+    let max_distance = if query.len() >= 8 { 2 } else { 1 };
+    let mut best: Option<(usize, usize, usize)> = None;
+
+    for start in (0..lower.len()).filter(|index| is_word_start(chars, *index)) {
+        for length in query.len() - 1..=query.len() + 1 {
+            let Some(candidate) = lower.get(start..start + length) else {
+                continue;
+            };
+
+            let distance = get_edit_distance(candidate, query);
+
+            if distance <= max_distance && best.is_none_or(|(current, _, _)| distance < current) {
+                best = Some((distance, start, start + length));
+            }
+        }
+    }
+
+    let (distance, start, end) = best?;
+
+    Some(Match {
+        score: 30.0 - 8.0 * distance as f64,
+        ranges: vec![(start, end)],
+    })
+}
+
+/// Edits between two strings, where swapping two neighbour characters counts as one
+fn get_edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// Start of the text, a character after a separator or an uppercase letter after a lowercase
+/// one, as in `camelCase`
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    let Some(previous) = index.checked_sub(1).map(|previous| chars[previous]) else {
+        return true;
+    };
+
+    !previous.is_alphanumeric() || (previous.is_lowercase() && chars[index].is_uppercase())
+}
+
+/// Lowercase that keeps one char per char, so indexes stay the same as in the text
+fn to_lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort();
+
+    let mut merged: Vec<(usize, usize)> = vec![];
+
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(text: &str, query: &str) -> f64 {
+        fuzzy_match(text, query).unwrap().score
+    }
+
+    fn ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+        fuzzy_match(text, query).unwrap().ranges
+    }
+
+    #[test]
+    fn matches_everything_with_empty_query() {
+        assert_eq!(
+            fuzzy_match("Firefox", "  "),
+            Some(Match {
+                score: 0.0,
+                ranges: vec![]
+            })
+        );
+    }
+
+    #[test]
+    fn matches_exact_text_ignoring_case() {
+        assert_eq!(score("Firefox", "FIREFOX"), 100.0);
+        assert_eq!(ranges("Firefox", "firefox"), vec![(0, 7)]);
+    }
+
+    #[test]
+    fn ranks_prefix_over_word_start_over_inside() {
+        let prefix = score("Studio Code", "stu");
+        let word_start = score("Visual Studio", "stu");
+        let inside = score("Mustudio", "stu");
+
+        assert!(score("Studio", "studio") > prefix);
+        assert!(prefix > word_start);
+        assert!(word_start > inside);
+
+        assert_eq!(ranges("Visual Studio", "stu"), vec![(7, 10)]);
+        assert_eq!(ranges("Mustudio", "stu"), vec![(2, 5)]);
+    }
+
+    #[test]
+    fn prefers_shorter_texts_for_same_prefix() {
+        assert!(score("Code", "cod") > score("Codeblocks", "cod"));
+    }
+
+    #[test]
+    fn matches_camel_case_word_starts() {
+        assert_eq!(ranges("GitGui", "gg"), vec![(0, 1), (3, 4)]);
+        assert_eq!(ranges("GitGui", "gui"), vec![(3, 6)]);
+        assert!(score("GitGui", "gui") > score("Linguist", "gui"));
+    }
+
+    #[test]
+    fn matches_acronyms() {
+        assert_eq!(
+            ranges("Visual Studio Code", "vsc"),
+            vec![(0, 1), (7, 8), (14, 15)]
+        );
+
+        // Ranked above the same letters found anywhere in the text
+        assert!(score("Visual Studio Code", "vsc") > score("Divisicon", "vsc"));
+    }
+
+    #[test]
+    fn matches_subsequences() {
+        assert_eq!(ranges("Firefox", "ffx"), vec![(0, 1), (4, 5), (6, 7)]);
+        assert!(score("Firefox", "ffx") < score("Firefox", "fox"));
+    }
+
+    #[test]
+    fn allows_no_typos_below_four_chars() {
+        assert_eq!(fuzzy_match("Firefox", "fri"), None);
+    }
+
+    #[test]
+    fn allows_one_typo_from_four_chars() {
+        assert_eq!(ranges("Firefox", "fierfox"), vec![(0, 7)]);
+        assert_eq!(fuzzy_match("Firefox", "fierfxo"), None);
+    }
+
+    #[test]
+    fn allows_two_typos_from_eight_chars() {
+        assert_eq!(ranges("Thunderbird", "thnuderbrid"), vec![(0, 11)]);
+        assert_eq!(fuzzy_match("Thunderbird", "thnudrebrid"), None);
+    }
+
+    #[test]
+    fn ranks_typos_below_real_matches() {
+        assert!(score("Firefox", "fierfox") < score("Firefox", "ffx"));
+    }
+
+    #[test]
+    fn matches_words_apart() {
+        assert_eq!(
+            ranges("Visual Studio Code", "code visual"),
+            vec![(0, 6), (14, 18)]
+        );
+        assert_eq!(fuzzy_match("Visual Studio Code", "code vim"), None);
+    }
+
+    #[test]
+    fn ranks_words_together_over_words_apart() {
+        assert!(
+            score("Visual Studio Code", "studio code") > score("Visual Studio Code", "code studio")
+        );
+    }
+
+    #[test]
+    fn uses_char_indexes_for_non_ascii_text() {
+        assert_eq!(ranges("Ärger Öffnen", "öff"), vec![(6, 9)]);
+        assert_eq!(ranges("Café Noir", "noir"), vec![(5, 9)]);
+        assert_eq!(ranges("Éditeur d'Écran", "éé"), vec![(0, 1), (10, 11)]);
+    }
+
+    #[test]
+    fn offsets_ranges() {
+        let found = fuzzy_match("Firefox", "fox").unwrap().offset(2);
+
+        assert_eq!(found.ranges, vec![(6, 9)]);
+    }
+}
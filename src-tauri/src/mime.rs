@@ -0,0 +1,335 @@
+//! MIME type detection and app associations following the XDG MIME Applications spec
+//! https://specifications.freedesktop.org/mime-apps-spec/latest/
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::apps::resolve::{get_application_dirs, get_current_desktops};
+
+/// Associations from every `mimeapps.list`, merged by precedence
+#[derive(Debug, Clone, Default)]
+pub struct MimeApps {
+    pub defaults: HashMap<String, Vec<String>>,
+    pub added: HashMap<String, Vec<String>>,
+    pub removed: HashMap<String, Vec<String>>,
+}
+
+impl MimeApps {
+    pub fn load() -> Self {
+        let mut mime_apps = MimeApps::default();
+
+        for path in get_mimeapps_paths() {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            for (section, mime_type, ids) in parse_desktop_ids(&content) {
+                let associations = match section.as_str() {
+                    "Default Applications" => &mut mime_apps.defaults,
+                    "Added Associations" => &mut mime_apps.added,
+                    "Removed Associations" => &mut mime_apps.removed,
+                    _ => continue,
+                };
+
+                let known_ids = associations.entry(mime_type).or_default();
+
+                for id in ids {
+                    if !known_ids.contains(&id) {
+                        known_ids.push(id);
+                    }
+                }
+            }
+        }
+
+        mime_apps
+    }
+
+    pub fn get_default(&self, mime_type: &str) -> Option<&Vec<String>> {
+        self.defaults.get(mime_type)
+    }
+}
+
+/// Desktop IDs that handle `mime_type`, ordered by preference: defaults, added associations,
+/// then `mimeinfo.cache`. Removed associations are left out.
+pub fn get_associated_ids(mime_type: &str, mime_apps: &MimeApps) -> Vec<String> {
+    let removed = mime_apps.removed.get(mime_type);
+    let mut ids: Vec<String> = vec![];
+
+    let candidates = mime_apps
+        .defaults
+        .get(mime_type)
+        .into_iter()
+        .chain(mime_apps.added.get(mime_type))
+        .flatten()
+        .cloned()
+        .chain(get_mimeinfo_cache_ids(mime_type));
+
+    for id in candidates {
+        if removed.is_some_and(|removed| removed.contains(&id)) || ids.contains(&id) {
+            continue;
+        }
+
+        ids.push(id);
+    }
+
+    ids
+}
+
+fn get_mimeinfo_cache_ids(mime_type: &str) -> Vec<String> {
+    get_application_dirs()
+        .into_iter()
+        .filter_map(|dir| fs::read_to_string(dir.join("mimeinfo.cache")).ok())
+        .flat_map(|content| parse_desktop_ids(&content))
+        .filter(|(section, cache_type, _)| section == "MIME Cache" && cache_type == mime_type)
+        .flat_map(|(_, _, ids)| ids)
+        .collect()
+}
+
+/// `mimeapps.list` files from highest to lowest precedence
+pub fn get_mimeapps_paths() -> Vec<PathBuf> {
+    let desktops: Vec<String> = get_current_desktops()
+        .into_iter()
+        .map(|desktop| desktop.to_lowercase())
+        .collect();
+
+    let config_home = get_config_home();
+
+    let config_dirs = env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.trim().is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+
+    let mut paths: Vec<PathBuf> = vec![];
+
+    for dir in config_home
+        .into_iter()
+        .chain(env::split_paths(&config_dirs))
+    {
+        for desktop in &desktops {
+            paths.push(dir.join(format!("{desktop}-mimeapps.list")));
+        }
+
+        paths.push(dir.join("mimeapps.list"));
+    }
+
+    for dir in get_application_dirs() {
+        for desktop in &desktops {
+            paths.push(dir.join(format!("{desktop}-mimeapps.list")));
+        }
+
+        paths.push(dir.join("mimeapps.list"));
+    }
+
+    paths
+}
+
+pub fn get_config_home() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(dirs::config_dir)
+}
+
+/// Entries of a `key=id;id;` file as (section, key, ids)
+fn parse_desktop_ids(content: &str) -> Vec<(String, String, Vec<String>)> {
+    let mut section = String::new();
+    let mut entries = vec![];
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].to_string();
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let ids = value
+                .split(';')
+                .map(|id| id.trim())
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string())
+                .collect();
+
+            entries.push((section.clone(), key.trim().to_string(), ids));
+        }
+    }
+
+    entries
+}
+
+/// MIME type of a file from the shared-mime-info globs, falling back to `xdg-mime`
+pub fn get_file_mime_type(mime_globs: &MimeGlobs, path: &Path) -> String {
+    if path.is_dir() {
+        return "inode/directory".to_string();
+    }
+
+    if let Some(mime_type) = mime_globs.get_mime_type(path) {
+        return mime_type;
+    }
+
+    let output = Command::new("xdg-mime")
+        .args(["query", "filetype"])
+        .arg(path)
+        .output();
+
+    if let Ok(output) = output {
+        let mime_type = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        if output.status.success() && mime_type.contains('/') {
+            return mime_type;
+        }
+    }
+
+    "application/octet-stream".to_string()
+}
+
+/// Desktop file ID of the default app for the type as `xdg-mime` sees it, which also knows
+/// desktop specific defaults
+pub fn query_default_app(mime_type: &str) -> Option<String> {
+    let output = Command::new("xdg-mime")
+        .args(["query", "default", mime_type])
+        .output()
+        .ok()?;
+
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if output.status.success() && id.ends_with(".desktop") {
+        Some(id)
+    } else {
+        None
+    }
+}
+
+/// File name patterns of every MIME type, read once since typing a path looks them up on every
+/// keystroke
+pub struct MimeGlobs {
+    globs: Vec<Glob>,
+}
+
+impl MimeGlobs {
+    pub fn load() -> Self {
+        Self { globs: get_globs() }
+    }
+
+    /// Globs from the content of a `globs2` file instead of the installed ones
+    pub fn parse(content: &str) -> Self {
+        Self {
+            globs: parse_globs(content),
+        }
+    }
+
+    /// MIME type for a file name or an extension like `file.pdf`
+    pub fn get_mime_type(&self, path: &Path) -> Option<String> {
+        let file_name = path.file_name()?.to_string_lossy().to_string();
+        let lowercase_name = file_name.to_lowercase();
+
+        // (weight, pattern length, mime type)
+        let mut best: Option<(u32, usize, &str)> = None;
+
+        for glob in &self.globs {
+            let name = if glob.case_sensitive {
+                &file_name
+            } else {
+                &lowercase_name
+            };
+
+            if !glob_matches(&glob.pattern, name) {
+                continue;
+            }
+
+            let candidate = (glob.weight, glob.pattern.len(), glob.mime_type.as_str());
+
+            if best
+                .as_ref()
+                .is_none_or(|best| (candidate.0, candidate.1) > (best.0, best.1))
+            {
+                best = Some(candidate);
+            }
+        }
+
+        best.map(|(_, _, mime_type)| mime_type.to_string())
+    }
+}
+
+struct Glob {
+    weight: u32,
+    mime_type: String,
+    pattern: String,
+    case_sensitive: bool,
+}
+
+/// Globs from every `mime/globs2`, in the `weight:type:glob[:flags]` format
+fn get_globs() -> Vec<Glob> {
+    get_application_dirs()
+        .into_iter()
+        .filter_map(|dir| Some(dir.parent()?.join("mime/globs2")))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| parse_globs(&content))
+        .collect()
+}
+
+fn parse_globs(content: &str) -> Vec<Glob> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, ':');
+            let weight = parts.next()?.parse().ok()?;
+            let mime_type = parts.next()?.to_string();
+            let pattern = parts.next()?.to_string();
+            let case_sensitive = parts.next().is_some_and(|flags| flags.contains("cs"));
+
+            Some(Glob {
+                weight,
+                mime_type,
+                pattern: if case_sensitive {
+                    pattern
+                } else {
+                    pattern.to_lowercase()
+                },
+                case_sensitive,
+            })
+        })
+        .collect()
+}
+
+/// Matches the `*`, `?` and literal patterns used by shared-mime-info
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut pattern_index, mut name_index) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, name_index));
+                pattern_index += 1;
+            }
+            Some(char) if *char == '?' || *char == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_name_index)) => {
+                    pattern_index = star_index + 1;
+                    name_index = star_name_index + 1;
+                    backtrack = Some((star_index, star_name_index + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|char| *char == '*')
+}
@@ -1,21 +1,84 @@
-use std::{error::Error, fs, path::PathBuf};
+use std::{error::Error, fs, path::PathBuf, sync::atomic::AtomicBool};
 
-use sniffer_rs::sniffer::Sniffer;
 use tauri::Window;
 use tuicher_rs::{
     config::{get_config, write_config, BookmarkConfig, Config},
-    result::{Action, AddBookmark, Bookmark, OpenURL, RemoveBookmark, TUIResult},
+    result::{Action, AddBookmark, Bookmark, OpenURL, RemoveBookmark},
     utils::Query,
 };
 
+use crate::{
+    matcher::{fuzzy_match, Match},
+    plugins::SearchProvider,
+    result::{ResultAction, SearchResult},
+    state::AppState,
+};
+
+pub struct BookmarksProvider;
+
+impl SearchProvider for BookmarksProvider {
+    fn id(&self) -> &'static str {
+        "bookmarks"
+    }
+
+    fn default_keyword(&self) -> &'static str {
+        "b"
+    }
+
+    /// Enabled and keyword come from the tuicher config
+    fn keyword(&self, state: &AppState) -> Option<String> {
+        let config = state.config();
+
+        if config.enable_bookmarks {
+            Some(config.bookmarks_keyword.clone())
+        } else {
+            None
+        }
+    }
+
+    fn is_global(&self) -> bool {
+        true
+    }
+
+    fn query(
+        &self,
+        state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        get_bookmarks_results(&state.config(), query)
+    }
+
+    /// Only names and urls, the add and remove sub-commands need the keyword
+    fn query_global(
+        &self,
+        state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        Ok(get_bookmark_matches(&state.config(), query))
+    }
+
+    fn handle_action(
+        &self,
+        action: &ResultAction,
+        window: &Window,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        match action {
+            ResultAction::Core(Action::Bookmark(bookmark)) => {
+                Some(on_bookmark_action(bookmark.clone(), window.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
 pub fn get_bookmarks_results(
     config: &Config,
-    sniffer: &Sniffer,
     search_text: &str,
-) -> Result<Vec<TUIResult>, Box<dyn Error>> {
+) -> Result<Vec<SearchResult>, Box<dyn Error>> {
     let query = Query::new(search_text)?;
     let search_text = search_text.to_string();
-    let show_favicon = config.show_bookmarks_favicon.clone();
 
     if let Some(keyword) = &query.keyword {
         if keyword == "a" || keyword == "add" {
@@ -39,72 +102,81 @@ pub fn get_bookmarks_results(
                 "".to_string()
             };
 
-            return Ok(vec![TUIResult::new(
-                format!("Add: {name}"),
-                "bookmarks".to_string(),
-            )
-            .set_secondary_text(format!("URL: {url}"))
-            .set_action(Action::Bookmark(Bookmark::Add(AddBookmark {
-                name: name.clone(),
-                url: url.clone(),
-            })))]);
+            return Ok(vec![SearchResult::new(format!("Add: {name}"), "bookmarks")
+                .set_secondary_text(format!("URL: {url}"))
+                .set_action(Action::Bookmark(Bookmark::Add(AddBookmark {
+                    name: name.clone(),
+                    url: url.clone(),
+                })))]);
         }
 
         if keyword == "r" || keyword == "remove" {
-            let results: Vec<TUIResult> =
-                config
-                    .bookmarks
-                    .clone()
-                    .into_iter()
-                    .filter_map(|bookmark| {
-                        if sniffer.matches(&bookmark.name.clone(), &query.get_query()) {
-                            Some(
-                                TUIResult::new(
-                                    format!("Remove {}", &bookmark.name),
-                                    "bookmarks".to_string(),
-                                )
-                                .set_secondary_text(&bookmark.url)
-                                .set_action(Action::Bookmark(Bookmark::Remove(RemoveBookmark {
-                                    id: bookmark.id.clone(),
-                                }))),
-                            )
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
+            let results: Vec<SearchResult> = config
+                .bookmarks
+                .iter()
+                .filter_map(|bookmark| {
+                    let found = fuzzy_match(&bookmark.name, &query.get_query())?;
+
+                    Some(
+                        SearchResult::new(format!("Remove {}", &bookmark.name), "bookmarks")
+                            .set_secondary_text(&bookmark.url)
+                            .set_action(Action::Bookmark(Bookmark::Remove(RemoveBookmark {
+                                id: bookmark.id.clone(),
+                            })))
+                            .set_score(found.score)
+                            .set_highlights(found.offset("Remove ".len()).ranges),
+                    )
+                })
+                .collect();
 
             return Ok(results);
         }
     }
 
-    let mut results: Vec<TUIResult> = config
+    Ok(get_bookmark_matches(config, &search_text))
+}
+
+/// Bookmarks whose name or url matches, best first. Only the name is highlighted, since the
+/// url is the secondary text.
+pub fn get_bookmark_matches(config: &Config, search_text: &str) -> Vec<SearchResult> {
+    let show_favicon = config.show_bookmarks_favicon;
+
+    let mut results: Vec<SearchResult> = config
         .bookmarks
         .iter()
         .filter_map(|bookmark| {
-            if sniffer.matches(&bookmark.name, &search_text) {
-                let mut result = TUIResult::new(&bookmark.name, &"bookmarks".to_string())
-                    .set_secondary_text(&bookmark.url)
-                    .set_action(Action::OpenURL(OpenURL::new(&bookmark.url)));
-
-                if show_favicon {
-                    let image_path = get_favicon_path(&bookmark).ok()?;
-
-                    if image_path.exists() {
-                        result.set_icon_path(&image_path);
-                    }
+            let found = fuzzy_match(&bookmark.name, search_text).or_else(|| {
+                fuzzy_match(&bookmark.url, search_text).map(|found| Match {
+                    score: found.score * 0.9,
+                    ranges: vec![],
+                })
+            })?;
+
+            let mut result = SearchResult::new(&bookmark.name, "bookmarks")
+                .set_secondary_text(&bookmark.url)
+                .set_action(Action::OpenURL(OpenURL::new(&bookmark.url)))
+                .set_score(found.score)
+                .set_highlights(found.ranges);
+
+            if show_favicon {
+                let image_path = get_favicon_path(&bookmark).ok()?;
+
+                if image_path.exists() {
+                    result.set_icon_path(&image_path);
                 }
-
-                Some(result)
-            } else {
-                None
             }
+
+            Some(result)
         })
         .collect();
 
-    results.sort_by(|a, b| a.text.to_lowercase().cmp(&b.text.to_lowercase()));
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.text.to_lowercase().cmp(&b.text.to_lowercase()))
+    });
 
-    Ok(results)
+    results
 }
 
 pub fn on_bookmark_action(bookmark: Bookmark, window: Window) -> Result<(), Box<dyn Error>> {
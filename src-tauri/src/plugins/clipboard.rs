@@ -0,0 +1,594 @@
+use std::{
+    error::Error,
+    fs::{self, DirBuilder},
+    os::unix::fs::DirBuilderExt,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc, Mutex},
+};
+
+use bincode::{config, Decode, Encode};
+use tauri::{AppHandle, Manager, Window};
+use tuicher_rs::result::{Action, CopyImage, CopyText};
+
+use crate::{
+    clipboard::{watch_clipboard, ClipboardReader, OnChange},
+    config::ClipboardConfig,
+    history::now,
+    matcher::{fuzzy_match, Match},
+    plugins::SearchProvider,
+    result::{DeleteClipboardEntry, LauncherAction, PinClipboardEntry, ResultAction, SearchResult},
+    state::AppState,
+    typing::is_pasting,
+    utils::write_private_file,
+};
+
+/// Formats kept as images, in order of preference, with the extension of their file
+const IMAGE_TYPES: [(&str, &str); 5] = [
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/webp", "webp"),
+    ("image/gif", "gif"),
+    ("image/bmp", "bmp"),
+];
+
+/// Formats kept as text, in order of preference
+const TEXT_TYPES: [&str; 4] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+];
+
+/// Characters of a text entry shown in its result
+const PREVIEW_LENGTH: usize = 80;
+
+/// Held while the history is read, changed and written, so the watcher and the actions don't
+/// overwrite each other's changes
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Why copies aren't being recorded, returned by clipboard searches until a copy is recorded
+static RECORD_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Encode, Decode, Debug, Clone, Default)]
+pub struct ClipboardHistory {
+    /// Newest first
+    pub entries: Vec<ClipboardEntry>,
+}
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct ClipboardEntry {
+    /// Hash of the content, so copying the same thing again moves its entry to the top
+    pub id: String,
+    pub content: ClipboardContent,
+    pub pinned: bool,
+    /// Unix timestamp in seconds of the last copy
+    pub copied_at: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub enum ClipboardContent {
+    Text(String),
+    /// Stored in a file named after the entry id, see [`get_image_path`]
+    Image {
+        mime_type: String,
+        size: u64,
+    },
+}
+
+impl ClipboardHistory {
+    /// Puts the content on top. Unpinned entries past `max_entries` are dropped with their
+    /// images in `dir`.
+    pub fn add(&mut self, id: String, content: ClipboardContent, max_entries: usize, dir: &Path) {
+        let pinned = self.remove(&id).is_some_and(|entry| entry.pinned);
+
+        self.entries.insert(
+            0,
+            ClipboardEntry {
+                id,
+                content,
+                pinned,
+                copied_at: now(),
+            },
+        );
+
+        let mut unpinned = 0;
+        let mut dropped: Vec<ClipboardEntry> = vec![];
+
+        self.entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+
+            unpinned += 1;
+
+            if unpinned > max_entries {
+                dropped.push(entry.clone());
+                return false;
+            }
+
+            true
+        });
+
+        for entry in &dropped {
+            remove_image(dir, entry);
+        }
+    }
+
+    /// Removes the entry, but not its image
+    fn remove(&mut self, id: &str) -> Option<ClipboardEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index))
+    }
+}
+
+pub struct ClipboardProvider;
+
+impl SearchProvider for ClipboardProvider {
+    fn id(&self) -> &'static str {
+        "clipboard"
+    }
+
+    fn default_keyword(&self) -> &'static str {
+        "cb"
+    }
+
+    fn query(
+        &self,
+        state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        if let Some(error) = RECORD_ERROR.lock().unwrap().clone() {
+            return Err(error.into());
+        }
+
+        get_clipboard_results(&state.clipboard_history(), query)
+    }
+
+    fn handle_action(
+        &self,
+        action: &ResultAction,
+        window: &Window,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let ResultAction::Launcher(action) = action else {
+            return None;
+        };
+
+        match action {
+            LauncherAction::PinClipboardEntry(pin) => {
+                Some(on_pin_clipboard_entry(pin.clone(), window.clone()))
+            }
+            LauncherAction::DeleteClipboardEntry(delete) => {
+                Some(on_delete_clipboard_entry(delete.clone(), window.clone()))
+            }
+            LauncherAction::ClearClipboardHistory => {
+                Some(on_clear_clipboard_history(window.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Entries matching the query, pinned ones first. Selecting one copies it again.
+pub fn get_clipboard_results(
+    history: &ClipboardHistory,
+    query: &str,
+) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let now = now();
+    let dir = get_clipboard_dir()?;
+
+    let mut entries: Vec<&ClipboardEntry> = history.entries.iter().collect();
+    entries.sort_by_key(|entry| !entry.pinned);
+
+    let results = entries
+        .into_iter()
+        .filter_map(|entry| get_entry_result(entry, query, now, &dir))
+        .collect();
+
+    Ok(results)
+}
+
+fn get_entry_result(
+    entry: &ClipboardEntry,
+    query: &str,
+    now: u64,
+    dir: &Path,
+) -> Option<SearchResult> {
+    let age = format_age(now.saturating_sub(entry.copied_at));
+
+    let mut result = match &entry.content {
+        ClipboardContent::Text(text) => {
+            let preview = get_preview(text);
+
+            // Only the preview is shown, so the rest of the text is matched without ranges
+            let found = fuzzy_match(&preview, query).or_else(|| {
+                text.to_lowercase()
+                    .contains(&query.trim().to_lowercase())
+                    .then_some(Match {
+                        score: 30.0,
+                        ranges: vec![],
+                    })
+            })?;
+
+            let lines = text.lines().count();
+
+            let details = if lines > 1 {
+                format!("{lines} lines · {age}")
+            } else {
+                format!("{} characters · {age}", text.chars().count())
+            };
+
+            SearchResult::new(preview, "clipboard")
+                .set_secondary_text(details)
+                .set_action(Action::CopyText(CopyText::new(text)))
+                .set_score(found.score)
+                .set_highlights(found.ranges)
+        }
+        ClipboardContent::Image { mime_type, size } => {
+            let found = fuzzy_match("Image", query).or_else(|| fuzzy_match(mime_type, query))?;
+
+            let path = get_image_path(dir, &entry.id, mime_type);
+            let kind = mime_type.trim_start_matches("image/").to_uppercase();
+
+            let mut result = SearchResult::new("Image", "clipboard")
+                .set_secondary_text(format!("{kind} · {} KB · {age}", size.div_ceil(1024)))
+                .set_action(Action::CopyImage(CopyImage::new(&path)))
+                .set_score(found.score);
+
+            // The image is its own thumbnail
+            result.set_icon_path(&path);
+            result
+        }
+    };
+
+    if entry.pinned {
+        result = result.set_badge("Pinned").add_action(
+            "Unpin",
+            LauncherAction::PinClipboardEntry(PinClipboardEntry::new(&entry.id, false)),
+        );
+    } else {
+        result = result.add_action(
+            "Pin",
+            LauncherAction::PinClipboardEntry(PinClipboardEntry::new(&entry.id, true)),
+        );
+    }
+
+    Some(
+        result
+            .add_action(
+                "Delete",
+                LauncherAction::DeleteClipboardEntry(DeleteClipboardEntry::new(&entry.id)),
+            )
+            .add_action("Clear History", LauncherAction::ClearClipboardHistory),
+    )
+}
+
+/// First non-empty line of the text, shortened to [`PREVIEW_LENGTH`]
+fn get_preview(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+
+    if line.chars().count() > PREVIEW_LENGTH {
+        format!("{}…", line.chars().take(PREVIEW_LENGTH).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => "Just now".to_string(),
+        60..3600 => format!("{} min ago", seconds / 60),
+        3600..86400 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
+
+/// Records every copy in the history while the provider is enabled
+pub fn setup_clipboard_watcher(app: AppHandle) {
+    let on_change: OnChange = Arc::new(move |reader| {
+        let recorded = reader.and_then(|reader| record_clipboard(&app, reader));
+
+        *RECORD_ERROR.lock().unwrap() = recorded
+            .err()
+            .map(|e| format!("Failed to record the clipboard: {e}"));
+    });
+
+    if let Err(e) = watch_clipboard(on_change) {
+        *RECORD_ERROR.lock().unwrap() = Some(format!("Clipboard history is unavailable: {e}"));
+    }
+}
+
+fn record_clipboard(app: &AppHandle, reader: &dyn ClipboardReader) -> Result<(), Box<dyn Error>> {
+    let state = app.state::<AppState>();
+
+    if ClipboardProvider.keyword(&state).is_none() || is_pasting() {
+        return Ok(());
+    }
+
+    let config: ClipboardConfig = state.launcher_config().clipboard.clone();
+    let mime_types = reader.mime_types()?;
+
+    // Password managers mark secrets with a hint format, their content is never read
+    if mime_types
+        .iter()
+        .any(|mime_type| config.ignored_mime_types.contains(mime_type))
+    {
+        return Ok(());
+    }
+
+    let image_type = IMAGE_TYPES
+        .iter()
+        .find(|(mime_type, _)| mime_types.iter().any(|offered| offered == mime_type));
+
+    let text_type = TEXT_TYPES
+        .iter()
+        .find(|mime_type| mime_types.iter().any(|offered| offered == *mime_type));
+
+    let (id, content) = if let Some((mime_type, _)) = image_type {
+        let data = reader.read(mime_type)?;
+
+        if data.len() > config.max_entry_size {
+            return Ok(());
+        }
+
+        let id = get_content_id(&data);
+        write_private_file(
+            &get_image_path(&get_clipboard_dir()?, &id, mime_type),
+            &data,
+        )?;
+
+        let content = ClipboardContent::Image {
+            mime_type: mime_type.to_string(),
+            size: data.len() as u64,
+        };
+
+        (id, content)
+    } else if let Some(mime_type) = text_type {
+        let data = reader.read(mime_type)?;
+
+        if data.len() > config.max_entry_size {
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(&data).to_string();
+
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+
+        (get_content_id(&data), ClipboardContent::Text(text))
+    } else {
+        return Ok(());
+    };
+
+    update_clipboard_history(&state, |history, dir| {
+        history.add(id, content, config.max_entries, dir);
+        Ok(())
+    })
+}
+
+fn on_pin_clipboard_entry(action: PinClipboardEntry, window: Window) -> Result<(), Box<dyn Error>> {
+    update_clipboard_history(&window.state::<AppState>(), |history, _| {
+        let entry = history
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == action.id)
+            .ok_or_else(|| "Clipboard entry not found")?;
+
+        entry.pinned = action.pinned;
+        Ok(())
+    })?;
+
+    window.close()?;
+    Ok(())
+}
+
+fn on_delete_clipboard_entry(
+    action: DeleteClipboardEntry,
+    window: Window,
+) -> Result<(), Box<dyn Error>> {
+    update_clipboard_history(&window.state::<AppState>(), |history, dir| {
+        let entry = history
+            .remove(&action.id)
+            .ok_or_else(|| "Clipboard entry not found")?;
+
+        remove_image(dir, &entry);
+        Ok(())
+    })?;
+
+    window.close()?;
+    Ok(())
+}
+
+/// Removes every entry but the pinned ones
+fn on_clear_clipboard_history(window: Window) -> Result<(), Box<dyn Error>> {
+    update_clipboard_history(&window.state::<AppState>(), |history, dir| {
+        history.entries.retain(|entry| {
+            if !entry.pinned {
+                remove_image(dir, entry);
+            }
+
+            entry.pinned
+        });
+
+        Ok(())
+    })?;
+
+    window.close()?;
+    Ok(())
+}
+
+/// Changes the history, which is given with the clipboard dir for its images
+fn update_clipboard_history(
+    state: &AppState,
+    update: impl FnOnce(&mut ClipboardHistory, &Path) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = get_clipboard_dir()?;
+    let mut history = (*state.clipboard_history()).clone();
+
+    update(&mut history, &dir)?;
+
+    write_clipboard_history(&dir, &history)?;
+    state.set_clipboard_history(history);
+
+    Ok(())
+}
+
+pub fn get_clipboard_history() -> Result<ClipboardHistory, Box<dyn Error>> {
+    let path = get_clipboard_dir()?.join("history.bin");
+
+    if !path.exists() {
+        return Ok(ClipboardHistory::default());
+    }
+
+    let bytes = fs::read(path)?;
+    let (history, _): (ClipboardHistory, usize) =
+        bincode::decode_from_slice(&bytes, config::standard())?;
+
+    Ok(history)
+}
+
+fn write_clipboard_history(dir: &Path, history: &ClipboardHistory) -> Result<(), Box<dyn Error>> {
+    let bytes = bincode::encode_to_vec(history, config::standard())?;
+    write_private_file(&dir.join("history.bin"), &bytes)
+}
+
+/// Image of the entry in the clipboard dir
+pub fn get_image_path(dir: &Path, id: &str, mime_type: &str) -> PathBuf {
+    let extension = IMAGE_TYPES
+        .iter()
+        .find(|(image_type, _)| *image_type == mime_type)
+        .map(|(_, extension)| *extension)
+        .unwrap_or("img");
+
+    dir.join(format!("{id}.{extension}"))
+}
+
+fn remove_image(dir: &Path, entry: &ClipboardEntry) {
+    if let ClipboardContent::Image { mime_type, .. } = &entry.content {
+        let _ = fs::remove_file(get_image_path(dir, &entry.id, mime_type));
+    }
+}
+
+/// FNV-1a hash of the content, stable across runs unlike the std hasher
+fn get_content_id(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{hash:016x}")
+}
+
+fn get_clipboard_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let path = dirs::data_dir()
+        .ok_or_else(|| "Failed to get data dir")?
+        .join("tuicher")
+        .join("clipboard");
+
+    if !path.exists() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&path)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn text(text: &str) -> ClipboardContent {
+        ClipboardContent::Text(text.to_string())
+    }
+
+    fn ids(history: &ClipboardHistory) -> Vec<&str> {
+        history
+            .entries
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn adds_newest_first() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::default();
+
+        history.add("a".to_string(), text("a"), 10, dir.path());
+        history.add("b".to_string(), text("b"), 10, dir.path());
+
+        assert_eq!(ids(&history), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn moves_copied_again_entry_to_top_keeping_its_pin() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::default();
+
+        history.add("a".to_string(), text("a"), 10, dir.path());
+        history.entries[0].pinned = true;
+        history.add("b".to_string(), text("b"), 10, dir.path());
+        history.add("a".to_string(), text("a"), 10, dir.path());
+
+        assert_eq!(ids(&history), vec!["a", "b"]);
+        assert!(history.entries[0].pinned);
+    }
+
+    #[test]
+    fn drops_oldest_unpinned_entries_past_the_cap() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::default();
+
+        for id in ["a", "b", "c", "d"] {
+            history.add(id.to_string(), text(id), 2, dir.path());
+        }
+
+        assert_eq!(ids(&history), vec!["d", "c"]);
+    }
+
+    #[test]
+    fn keeps_pinned_entries_past_the_cap() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::default();
+
+        history.add("a".to_string(), text("a"), 2, dir.path());
+        history.entries[0].pinned = true;
+
+        for id in ["b", "c", "d"] {
+            history.add(id.to_string(), text(id), 2, dir.path());
+        }
+
+        assert_eq!(ids(&history), vec!["d", "c", "a"]);
+    }
+
+    #[test]
+    fn removes_images_of_dropped_entries() {
+        let dir = TempDir::new().unwrap();
+        let mut history = ClipboardHistory::default();
+        let image = || ClipboardContent::Image {
+            mime_type: "image/png".to_string(),
+            size: 3,
+        };
+
+        let old_path = get_image_path(dir.path(), "old-image", "image/png");
+        let new_path = get_image_path(dir.path(), "new-image", "image/png");
+        fs::write(&old_path, [1, 2, 3]).unwrap();
+        fs::write(&new_path, [1, 2, 3]).unwrap();
+
+        history.add("old-image".to_string(), image(), 1, dir.path());
+        history.add("new-image".to_string(), image(), 1, dir.path());
+
+        assert_eq!(ids(&history), vec!["new-image"]);
+        assert!(!old_path.exists());
+        assert!(new_path.exists());
+    }
+}
@@ -0,0 +1,320 @@
+use std::{error::Error, fs, iter, path::Path, sync::atomic::AtomicBool};
+
+use tauri::Window;
+
+use crate::{
+    apps::{search::get_file_handlers, App},
+    matcher::fuzzy_match,
+    mime::{get_config_home, MimeApps, MimeGlobs},
+    plugins::SearchProvider,
+    result::{LauncherAction, ResultAction, SearchResult, SetDefaultApp},
+    state::AppState,
+};
+
+const DEFAULT_APPLICATIONS: &str = "[Default Applications]";
+
+pub struct DefaultsProvider;
+
+impl SearchProvider for DefaultsProvider {
+    fn id(&self) -> &'static str {
+        "defaults"
+    }
+
+    fn default_keyword(&self) -> &'static str {
+        "default"
+    }
+
+    fn query(
+        &self,
+        state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        Ok(get_defaults_results(
+            &state.apps(),
+            &state.matchers.mime_globs,
+            query,
+        ))
+    }
+
+    fn handle_action(
+        &self,
+        action: &ResultAction,
+        window: &Window,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        match action {
+            ResultAction::Launcher(LauncherAction::SetDefaultApp(set_default_app)) => {
+                Some(on_set_default_app(set_default_app.clone(), window.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Shows the default app of a MIME type or extension and every app that can handle it
+pub fn get_defaults_results(
+    apps: &[App],
+    mime_globs: &MimeGlobs,
+    search_text: &str,
+) -> Vec<SearchResult> {
+    let mut parts = search_text.split_whitespace();
+
+    let Some(mime_type) = parts
+        .next()
+        .and_then(|text| get_mime_type(mime_globs, text))
+    else {
+        return vec![];
+    };
+
+    let app_filter = parts.collect::<Vec<&str>>().join(" ");
+    let mime_apps = MimeApps::load();
+
+    let default_app = mime_apps.get_default(&mime_type).and_then(|ids| {
+        ids.iter()
+            .find_map(|id| apps.iter().find(|app| &app.id == id))
+    });
+
+    let mut default_result = SearchResult::new(
+        match default_app {
+            Some(app) => format!("Default: {}", &app.name),
+            None => "No default app".to_string(),
+        },
+        "app",
+    )
+    .set_secondary_text(&mime_type);
+
+    if let Some(icon_path) = default_app.and_then(|app| app.icon_path.as_ref()) {
+        default_result.set_icon_path(icon_path);
+    }
+
+    let mut results: Vec<SearchResult> = vec![];
+
+    for app in get_file_handlers(apps, &mime_apps, &mime_type) {
+        if default_app.is_some_and(|default_app| default_app.id == app.id) {
+            continue;
+        }
+
+        // The name is shown after `Set `
+        let Some(found) = fuzzy_match(&app.name, &app_filter) else {
+            continue;
+        };
+
+        let found = found.offset("Set ".len());
+
+        let mut result = SearchResult::new(format!("Set {} as default", &app.name), "app")
+            .set_secondary_text(format!("Open {mime_type} with {}", &app.name))
+            .set_action(LauncherAction::SetDefaultApp(SetDefaultApp {
+                mime_type: mime_type.clone(),
+                id: app.id.clone(),
+            }))
+            .set_score(found.score)
+            .set_highlights(found.ranges);
+
+        if let Some(icon_path) = &app.icon_path {
+            result.set_icon_path(icon_path);
+        }
+
+        results.push(result);
+    }
+
+    // Stable, so without a filter the handlers keep their order. The default stays first.
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.insert(0, default_result);
+
+    results
+}
+
+/// Accepts a MIME type like `application/pdf` or an extension like `pdf` or `.pdf`
+fn get_mime_type(mime_globs: &MimeGlobs, text: &str) -> Option<String> {
+    if text.contains('/') {
+        return Some(text.to_lowercase());
+    }
+
+    let extension = text.trim_start_matches('.');
+
+    if extension.is_empty() {
+        return None;
+    }
+
+    mime_globs.get_mime_type(Path::new(&format!("file.{extension}")))
+}
+
+pub fn on_set_default_app(action: SetDefaultApp, window: Window) -> Result<(), Box<dyn Error>> {
+    let path = get_config_home()
+        .ok_or_else(|| "Failed to get config dir")?
+        .join("mimeapps.list");
+
+    // Dotfiles often link the file, the rename below would replace the link with a copy
+    let path = fs::canonicalize(&path).unwrap_or(path);
+
+    let content = if path.exists() {
+        fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+
+    let content = set_default_app(&content, &action.mime_type, &action.id);
+
+    let temp_path = path.with_extension("list.tmp");
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, &path)?;
+
+    window.close()?;
+    Ok(())
+}
+
+/// Sets the default in the `[Default Applications]` group, keeping every other line as it is.
+/// An existing line of the MIME type keeps its spacing, and the apps it listed stay after the
+/// new default as fallbacks.
+fn set_default_app(content: &str, mime_type: &str, id: &str) -> String {
+    let new_line = format!("{mime_type}={id};");
+    let mut lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
+
+    let section_start = lines
+        .iter()
+        .position(|line| line.trim() == DEFAULT_APPLICATIONS);
+
+    let Some(section_start) = section_start else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+
+        lines.push(DEFAULT_APPLICATIONS.to_string());
+        lines.push(new_line);
+
+        return lines.join("\n") + "\n";
+    };
+
+    let section_end = lines
+        .iter()
+        .skip(section_start + 1)
+        .position(|line| line.trim().starts_with('['))
+        .map(|position| section_start + 1 + position)
+        .unwrap_or(lines.len());
+
+    let existing = (section_start + 1..section_end).find(|index| {
+        lines[*index]
+            .split_once('=')
+            .is_some_and(|(key, _)| key.trim() == mime_type)
+    });
+
+    match existing {
+        Some(index) => lines[index] = set_first_id(&lines[index], id),
+        None => {
+            // Keep blank lines between groups after the new entry
+            let mut insert_at = section_end;
+
+            while insert_at > section_start + 1 && lines[insert_at - 1].trim().is_empty() {
+                insert_at -= 1;
+            }
+
+            lines.insert(insert_at, new_line);
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// The `key=id;id;` line with `id` first and without its other occurrences
+fn set_first_id(line: &str, id: &str) -> String {
+    let (key, value) = line.split_once('=').unwrap_or((line, ""));
+    let spacing = &value[..value.len() - value.trim_start().len()];
+
+    let ids: String = iter::once(id)
+        .chain(
+            value
+                .split(';')
+                .map(|other| other.trim())
+                .filter(|other| !other.is_empty() && *other != id),
+        )
+        .map(|id| format!("{id};"))
+        .collect();
+
+    format!("{key}={spacing}{ids}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_group_in_empty_file() {
+        assert_eq!(
+            set_default_app("", "text/plain", "gedit.desktop"),
+            "[Default Applications]\ntext/plain=gedit.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn replaces_existing_default() {
+        let content = "[Default Applications]\n\
+            text/plain=gedit.desktop;\n\
+            image/png=gimp.desktop;\n";
+
+        assert_eq!(
+            set_default_app(content, "text/plain", "code.desktop"),
+            "[Default Applications]\n\
+            text/plain=code.desktop;gedit.desktop;\n\
+            image/png=gimp.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn moves_listed_app_first() {
+        let content = "[Default Applications]\n\
+            text/plain=gedit.desktop;code.desktop;vim.desktop;\n";
+
+        assert_eq!(
+            set_default_app(content, "text/plain", "code.desktop"),
+            "[Default Applications]\n\
+            text/plain=code.desktop;gedit.desktop;vim.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn adds_default_at_end_of_group() {
+        let content = "[Default Applications]\n\
+            image/png=gimp.desktop;\n\
+            \n\
+            [Added Associations]\n\
+            text/plain=gedit.desktop;\n";
+
+        assert_eq!(
+            set_default_app(content, "text/plain", "code.desktop"),
+            "[Default Applications]\n\
+            image/png=gimp.desktop;\n\
+            text/plain=code.desktop;\n\
+            \n\
+            [Added Associations]\n\
+            text/plain=gedit.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn appends_group_after_other_groups() {
+        let content = "[Added Associations]\ntext/plain=gedit.desktop;\n";
+
+        assert_eq!(
+            set_default_app(content, "text/plain", "code.desktop"),
+            "[Added Associations]\n\
+            text/plain=gedit.desktop;\n\
+            \n\
+            [Default Applications]\n\
+            text/plain=code.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_spacing() {
+        let content = "# Managed by hand\n\
+            [Default Applications]\n\
+            text/html = firefox.desktop;\n";
+
+        assert_eq!(
+            set_default_app(content, "text/html", "chromium.desktop"),
+            "# Managed by hand\n\
+            [Default Applications]\n\
+            text/html = chromium.desktop;firefox.desktop;\n"
+        );
+    }
+}
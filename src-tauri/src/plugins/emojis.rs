@@ -1,32 +1,78 @@
-use sniffer_rs::sniffer::Sniffer;
-use tuicher_rs::result::{Action, CopyText, TUIResult};
+use std::{error::Error, sync::atomic::AtomicBool};
 
-pub fn get_emoji_results(sniffer: &Sniffer, search_text: &str) -> Vec<TUIResult> {
+use tuicher_rs::result::{Action, CopyText};
+
+use crate::{matcher::fuzzy_match, plugins::SearchProvider, result::SearchResult, state::AppState};
+
+/// Emojis are copied with the core copy action, so there are no actions to handle
+pub struct EmojisProvider;
+
+impl SearchProvider for EmojisProvider {
+    fn id(&self) -> &'static str {
+        "emojis"
+    }
+
+    fn default_keyword(&self) -> &'static str {
+        "e"
+    }
+
+    /// Enabled and keyword come from the tuicher config
+    fn keyword(&self, state: &AppState) -> Option<String> {
+        let config = state.config();
+
+        if config.enable_emojis {
+            Some(config.emojis_keyword.clone())
+        } else {
+            None
+        }
+    }
+
+    fn query(
+        &self,
+        _state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        Ok(get_emoji_results(query))
+    }
+}
+
+pub fn get_emoji_results(search_text: &str) -> Vec<SearchResult> {
     if search_text.trim().is_empty() {
         return vec![];
     }
 
-    let results = emojis::iter()
-        .into_iter()
+    let mut results: Vec<SearchResult> = emojis::iter()
         .filter_map(|emoji| {
-            let matches_codes = emoji
+            // The name is shown after the emoji and a space
+            let name_match = fuzzy_match(emoji.name(), search_text)
+                .map(|found| found.offset(emoji.as_str().chars().count() + 1));
+
+            // Shortcodes aren't shown, so they only add to the score
+            let code_score = emoji
                 .shortcodes()
-                .into_iter()
-                .any(|code| sniffer.matches(code, search_text));
-
-            if sniffer.matches(emoji.name(), search_text) || matches_codes {
-                return Some(
-                    TUIResult::new(
-                        format!("{} {}", emoji.as_str(), emoji.name()),
-                        "emojis".to_string(),
-                    )
-                    .set_action(Action::CopyText(CopyText::new(emoji.as_str()))),
-                );
-            }
+                .filter_map(|code| fuzzy_match(code, search_text))
+                .map(|found| found.score)
+                .max_by(|a, b| a.total_cmp(b));
 
-            None
+            let (score, highlights) = match (name_match, code_score) {
+                (Some(found), Some(code_score)) if code_score > found.score => {
+                    (code_score, found.ranges)
+                }
+                (Some(found), _) => (found.score, found.ranges),
+                (None, Some(code_score)) => (code_score, vec![]),
+                (None, None) => return None,
+            };
+
+            Some(
+                SearchResult::new(format!("{} {}", emoji.as_str(), emoji.name()), "emojis")
+                    .set_action(Action::CopyText(CopyText::new(emoji.as_str())))
+                    .set_score(score)
+                    .set_highlights(highlights),
+            )
         })
         .collect();
 
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
     results
 }
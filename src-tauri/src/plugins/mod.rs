@@ -1,3 +1,112 @@
+use std::{error::Error, sync::atomic::AtomicBool, time::Duration};
+
+use tauri::Window;
+
+use crate::{
+    apps::search::AppsProvider,
+    plugins::{
+        bookmarks::BookmarksProvider, clipboard::ClipboardProvider, defaults::DefaultsProvider,
+        emojis::EmojisProvider, session::SessionProvider, windows::WindowsProvider,
+    },
+    result::{ResultAction, SearchResult},
+    state::AppState,
+};
+
 pub mod bookmarks;
+pub mod clipboard;
+pub mod defaults;
 pub mod emojis;
 pub mod session;
+pub mod windows;
+
+/// A source of results, searched through its keyword or, when global, on every search. Adding a
+/// plugin means implementing this and listing it in [`get_providers`].
+pub trait SearchProvider: Send + Sync {
+    /// Stable id, used for the provider settings in the launcher config
+    fn id(&self) -> &'static str;
+
+    /// Keyword used when the config doesn't set one
+    fn default_keyword(&self) -> &'static str;
+
+    /// Keyword that selects the provider, or `None` when it is disabled
+    fn keyword(&self, state: &AppState) -> Option<String> {
+        let launcher_config = state.launcher_config();
+        let provider_config = launcher_config.providers.get(self.id());
+
+        if provider_config.is_some_and(|provider_config| !provider_config.enabled) {
+            return None;
+        }
+
+        let keyword = provider_config
+            .and_then(|provider_config| provider_config.keyword.clone())
+            .unwrap_or_else(|| self.default_keyword().to_string());
+
+        Some(keyword)
+    }
+
+    /// Whether the provider is also searched when no keyword is typed
+    fn is_global(&self) -> bool {
+        false
+    }
+
+    /// Most results added to searches without a keyword when the config doesn't set it
+    fn default_max_results(&self) -> usize {
+        5
+    }
+
+    fn max_results(&self, state: &AppState) -> usize {
+        state
+            .launcher_config()
+            .providers
+            .get(self.id())
+            .and_then(|provider_config| provider_config.max_results)
+            .unwrap_or_else(|| self.default_max_results())
+    }
+
+    /// How long a search waits for the provider before leaving its results out
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(500)
+    }
+
+    /// Results for the query, without the keyword. Their score is used to rank them against
+    /// the results of other providers. `cancelled` is set once a newer query replaces this
+    /// one, so slow providers can stop early.
+    fn query(
+        &self,
+        state: &AppState,
+        query: &str,
+        cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>>;
+
+    /// Results for a search without a keyword. Providers whose keyword takes sub-commands
+    /// override this, so typing one outside of the keyword never runs it.
+    fn query_global(
+        &self,
+        state: &AppState,
+        query: &str,
+        cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        self.query(state, query, cancelled)
+    }
+
+    /// Runs the action if it belongs to the provider. Returns `None` for other actions.
+    fn handle_action(
+        &self,
+        _action: &ResultAction,
+        _window: &Window,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        None
+    }
+}
+
+pub fn get_providers() -> Vec<Box<dyn SearchProvider>> {
+    vec![
+        Box::new(AppsProvider),
+        Box::new(SessionProvider),
+        Box::new(EmojisProvider),
+        Box::new(DefaultsProvider),
+        Box::new(WindowsProvider),
+        Box::new(BookmarksProvider),
+        Box::new(ClipboardProvider),
+    ]
+}
@@ -1,76 +1,139 @@
-use std::{env, error::Error, process::Command};
-
-use sniffer_rs::sniffer::Sniffer;
-use tauri::Window;
-use tuicher_rs::result::{Action, Session, TUIResult};
-
-pub fn get_session_results(
-    sniffer: &Sniffer,
-    search_text: &str,
-) -> Result<Vec<TUIResult>, Box<dyn Error>> {
-    let mut results: Vec<TUIResult> = vec![];
-
-    if sniffer.matches("shutdown/poweroff", search_text) {
-        results.push(
-            TUIResult::new("Shutdown", "shutdown").set_action(Action::Session(Session::Shutdown)),
-        );
+use std::{env, error::Error, process::Command, sync::atomic::AtomicBool};
+
+use tauri::{Manager, Window};
+use tuicher_rs::result::{Action, Session};
+
+use crate::{
+    launcher::process::spawn_detached,
+    matcher::fuzzy_match,
+    plugins::SearchProvider,
+    result::{ResultAction, SearchResult},
+    state::AppState,
+};
+
+pub struct SessionProvider;
+
+impl SearchProvider for SessionProvider {
+    fn id(&self) -> &'static str {
+        "session"
     }
 
-    if sniffer.matches("restart/reboot", search_text) {
-        results.push(
-            TUIResult::new("Restart", "restart").set_action(Action::Session(Session::Restart)),
-        );
+    fn default_keyword(&self) -> &'static str {
+        "s"
     }
 
-    if sniffer.matches("suspend", search_text) {
-        results.push(
-            TUIResult::new("Suspend", "suspend").set_action(Action::Session(Session::Suspend)),
-        );
+    /// Enabled and keyword come from the tuicher config
+    fn keyword(&self, state: &AppState) -> Option<String> {
+        let config = state.config();
+
+        if config.enable_session_manager {
+            Some(config.session_manager_keyword.clone())
+        } else {
+            None
+        }
     }
 
-    if sniffer.matches("logout", search_text) {
-        results
-            .push(TUIResult::new("Logout", "logout").set_action(Action::Session(Session::Logout)));
+    fn is_global(&self) -> bool {
+        true
     }
 
+    fn default_max_results(&self) -> usize {
+        2
+    }
+
+    fn query(
+        &self,
+        _state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        get_session_results(query)
+    }
+
+    fn handle_action(
+        &self,
+        action: &ResultAction,
+        window: &Window,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        match action {
+            ResultAction::Core(Action::Session(session)) => {
+                Some(on_session_action(session.clone(), window.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Session actions matching the query by their name or an alias, e.g. `poweroff`
+pub fn get_session_results(search_text: &str) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let actions = [
+        ("Shutdown", "poweroff", "shutdown", Session::Shutdown),
+        ("Restart", "reboot", "restart", Session::Restart),
+        ("Suspend", "sleep", "suspend", Session::Suspend),
+        ("Logout", "logoff", "logout", Session::Logout),
+    ];
+
+    let results = actions
+        .into_iter()
+        .filter_map(|(text, alias, info, session)| {
+            // Alias matches rank lower and have no ranges, since the alias isn't shown
+            let (score, highlights) = match fuzzy_match(text, search_text) {
+                Some(found) => (found.score, found.ranges),
+                None => (fuzzy_match(alias, search_text)?.score * 0.8, vec![]),
+            };
+
+            Some(
+                SearchResult::new(text, info)
+                    .set_action(Action::Session(session))
+                    .set_score(score)
+                    .set_highlights(highlights),
+            )
+        })
+        .collect();
+
     Ok(results)
 }
 
 pub fn on_session_action(session: Session, window: Window) -> Result<(), Box<dyn Error>> {
-    match session {
-        Session::Shutdown => {
-            Command::new("systemctl").arg("poweroff").spawn()?;
-        }
-        Session::Restart => {
-            Command::new("systemctl").arg("reboot").spawn()?;
-        }
-        Session::Suspend => {
-            Command::new("systemctl").arg("suspend").spawn()?;
-        }
+    let command = match session {
+        Session::Shutdown => systemctl("poweroff"),
+        Session::Restart => systemctl("reboot"),
+        Session::Suspend => systemctl("suspend"),
         Session::Logout => {
             let desktop_env = env::var("XDG_CURRENT_DESKTOP")?.to_lowercase();
 
             match desktop_env.as_str() {
                 "hyprland" => {
-                    Command::new("hyprctl")
-                        .arg("dispatch")
-                        .arg("exit")
-                        .spawn()?;
+                    let mut command = Command::new("hyprctl");
+                    command.arg("dispatch").arg("exit");
+                    command
                 }
                 "kde" => {
-                    Command::new("qdbus6")
-                        .args(["org.kde.Shutdown", "/Shutdown", "org.kde.Shutdown.logout"])
-                        .spawn()?;
+                    let mut command = Command::new("qdbus6");
+                    command.args(["org.kde.Shutdown", "/Shutdown", "org.kde.Shutdown.logout"]);
+                    command
                 }
                 _ => {
-                    Command::new("notify-send")
-                        .arg("Missing Config for This Environment")
-                        .spawn()?;
+                    let mut command = Command::new("notify-send");
+                    command.arg("Missing Config for This Environment");
+                    command
                 }
             }
         }
-    }
+    };
+
+    spawn_detached(
+        command,
+        "session",
+        &window.state::<AppState>().launcher_config(),
+    )?;
 
     window.close()?;
     Ok(())
 }
+
+fn systemctl(arg: &str) -> Command {
+    let mut command = Command::new("systemctl");
+    command.arg(arg);
+    command
+}
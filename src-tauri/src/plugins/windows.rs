@@ -0,0 +1,185 @@
+use std::{error::Error, process, sync::atomic::AtomicBool};
+
+use tauri::Window;
+
+use crate::{
+    apps::{running::is_app_window, App},
+    compositor::{Compositor, CompositorWindow},
+    matcher::{fuzzy_match, Match},
+    plugins::SearchProvider,
+    result::{CloseWindow, FocusWindow, LauncherAction, MoveWindow, ResultAction, SearchResult},
+    state::AppState,
+};
+
+/// Also handles focusing the windows of running apps from the app results
+pub struct WindowsProvider;
+
+impl SearchProvider for WindowsProvider {
+    fn id(&self) -> &'static str {
+        "windows"
+    }
+
+    fn default_keyword(&self) -> &'static str {
+        "w"
+    }
+
+    fn query(
+        &self,
+        state: &AppState,
+        query: &str,
+        _cancelled: &AtomicBool,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        match Compositor::detect() {
+            Some(compositor) => get_windows_results(&compositor, &state.apps(), query),
+            None => Ok(vec![SearchResult::new("No windows", "windows")
+                .set_secondary_text("Window switching needs Hyprland, sway or i3")]),
+        }
+    }
+
+    fn handle_action(
+        &self,
+        action: &ResultAction,
+        window: &Window,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let ResultAction::Launcher(action) = action else {
+            return None;
+        };
+
+        match action {
+            LauncherAction::FocusWindow(focus_window) => {
+                Some(on_focus_window(focus_window.clone(), window.clone()))
+            }
+            LauncherAction::MoveWindow(move_window) => {
+                Some(on_move_window(move_window.clone(), window.clone()))
+            }
+            LauncherAction::CloseWindow(close_window) => {
+                Some(on_close_window(close_window.clone(), window.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Open windows matching the query. A `>workspace` part moves the selected window there instead
+/// of focusing it, e.g. `term >3`.
+pub fn get_windows_results(
+    compositor: &Compositor,
+    apps: &[App],
+    search_text: &str,
+) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let mut target_workspace: Option<&str> = None;
+    let mut query_parts: Vec<&str> = vec![];
+
+    for part in search_text.split_whitespace() {
+        match part.strip_prefix('>') {
+            Some(workspace) if !workspace.is_empty() => target_workspace = Some(workspace),
+            _ => query_parts.push(part),
+        }
+    }
+
+    let query = query_parts.join(" ");
+    let own_pid = process::id();
+
+    let mut results: Vec<SearchResult> = compositor
+        .get_windows()?
+        .into_iter()
+        .filter(|window| window.pid != Some(own_pid))
+        .filter_map(|window| {
+            // Class matches rank lower and have no ranges, since the class isn't shown
+            let found = fuzzy_match(&window.title, &query).or_else(|| {
+                Some(Match {
+                    score: fuzzy_match(&window.class, &query)?.score * 0.8,
+                    ranges: vec![],
+                })
+            })?;
+
+            Some(get_window_result(&window, apps, target_workspace, found))
+        })
+        .collect();
+
+    // Stable, so an empty query keeps the compositor's order
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(results)
+}
+
+fn get_window_result(
+    window: &CompositorWindow,
+    apps: &[App],
+    target_workspace: Option<&str>,
+    found: Match,
+) -> SearchResult {
+    let app = apps.iter().find(|app| is_app_window(app, window));
+
+    let class = app
+        .map(|app| app.name.clone())
+        .unwrap_or_else(|| window.class.clone());
+
+    let (text, found) = match target_workspace {
+        Some(workspace) => (
+            format!("Move {} to workspace {workspace}", &window.title),
+            found.offset("Move ".len()),
+        ),
+        None => (window.title.clone(), found),
+    };
+
+    let focus = LauncherAction::FocusWindow(FocusWindow::new(&window.id));
+    let move_here = LauncherAction::MoveWindow(MoveWindow::new(&window.id, None));
+    let close = LauncherAction::CloseWindow(CloseWindow::new(&window.id));
+
+    let result = SearchResult::new(text, "windows")
+        .set_secondary_text(format!("{class} · Workspace {}", &window.workspace))
+        .set_score(found.score)
+        .set_highlights(found.ranges);
+
+    let mut result = match target_workspace {
+        Some(workspace) => result
+            .set_action(LauncherAction::MoveWindow(MoveWindow::new(
+                &window.id,
+                Some(workspace.to_string()),
+            )))
+            .add_action("Focus Window", focus),
+        None => result.set_action(focus),
+    };
+
+    result = result
+        .add_action("Move to Current Workspace", move_here)
+        .add_action("Close Window", close);
+
+    if let Some(icon_path) = app.and_then(|app| app.icon_path.as_ref()) {
+        result.set_icon_path(icon_path);
+    }
+
+    result
+}
+
+pub fn on_focus_window(action: FocusWindow, window: Window) -> Result<(), Box<dyn Error>> {
+    get_compositor()?.focus_window(&action.id)?;
+
+    window.close()?;
+    Ok(())
+}
+
+pub fn on_close_window(action: CloseWindow, window: Window) -> Result<(), Box<dyn Error>> {
+    get_compositor()?.close_window(&action.id)?;
+
+    window.close()?;
+    Ok(())
+}
+
+pub fn on_move_window(action: MoveWindow, window: Window) -> Result<(), Box<dyn Error>> {
+    let compositor = get_compositor()?;
+
+    compositor.move_window(&action.id, action.workspace.as_deref())?;
+
+    // A window brought to the current workspace is the one the user wants to use
+    if action.workspace.is_none() {
+        compositor.focus_window(&action.id)?;
+    }
+
+    window.close()?;
+    Ok(())
+}
+
+pub fn get_compositor() -> Result<Compositor, Box<dyn Error>> {
+    Ok(Compositor::detect().ok_or_else(|| "Window switching needs Hyprland, sway or i3")?)
+}
@@ -0,0 +1,369 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tuicher_rs::result::{Action, TUIResult};
+
+/// Actions handled by the launcher itself on top of the ones from tuicher-rs
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum LauncherAction {
+    OpenDesktopAction(OpenDesktopAction),
+    OpenWith(OpenWith),
+    OpenAppWithArgs(OpenAppWithArgs),
+    OpenAppWithProfile(OpenAppWithProfile),
+    SetDefaultProfile(SetDefaultProfile),
+    SetDefaultApp(SetDefaultApp),
+    ShowInFolder(ShowInFolder),
+    CopyFile(CopyFile),
+    FocusWindow(FocusWindow),
+    MoveWindow(MoveWindow),
+    CloseWindow(CloseWindow),
+    PinClipboardEntry(PinClipboardEntry),
+    DeleteClipboardEntry(DeleteClipboardEntry),
+    ClearClipboardHistory,
+    TypeText(TypeText),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenDesktopAction {
+    /// Path of the desktop file the action belongs to
+    pub path: PathBuf,
+    /// Action id from the `[Desktop Action <id>]` group
+    pub action: String,
+    /// Files or urls passed through the action's `Exec` field codes
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+impl OpenDesktopAction {
+    pub fn new(path: impl Into<PathBuf>, action: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            action: action.into(),
+            targets: vec![],
+        }
+    }
+
+    pub fn set_targets(mut self, targets: Vec<String>) -> Self {
+        self.targets = targets;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenWith {
+    /// Path of the desktop file of the app to open the file with
+    pub path: PathBuf,
+    pub file: PathBuf,
+}
+
+impl OpenWith {
+    pub fn new(path: impl Into<PathBuf>, file: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file: file.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAppWithArgs {
+    /// Path of the desktop file of the app
+    pub path: PathBuf,
+    /// Files or urls typed after the app name, passed through the `Exec` field codes
+    pub targets: Vec<String>,
+}
+
+impl OpenAppWithArgs {
+    pub fn new(path: impl Into<PathBuf>, targets: Vec<String>) -> Self {
+        Self {
+            path: path.into(),
+            targets,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenAppWithProfile {
+    /// Path of the desktop file of the app
+    pub path: PathBuf,
+    /// Name of the launch profile. `None` launches the app without any, even when it has a
+    /// default one.
+    pub profile: Option<String>,
+}
+
+impl OpenAppWithProfile {
+    pub fn new(path: impl Into<PathBuf>, profile: Option<String>) -> Self {
+        Self {
+            path: path.into(),
+            profile,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetDefaultProfile {
+    /// Path of the desktop file of the app
+    pub path: PathBuf,
+    /// Name of the launch profile. `None` forgets the default profile of the app.
+    pub profile: Option<String>,
+}
+
+impl SetDefaultProfile {
+    pub fn new(path: impl Into<PathBuf>, profile: Option<String>) -> Self {
+        Self {
+            path: path.into(),
+            profile,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetDefaultApp {
+    pub mime_type: String,
+    /// Desktop file ID of the new default app
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FocusWindow {
+    /// Compositor id of the window
+    pub id: String,
+}
+
+impl FocusWindow {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MoveWindow {
+    /// Compositor id of the window
+    pub id: String,
+    /// Workspace name or number. `None` is the current workspace.
+    pub workspace: Option<String>,
+}
+
+impl MoveWindow {
+    pub fn new(id: impl Into<String>, workspace: Option<String>) -> Self {
+        Self {
+            id: id.into(),
+            workspace,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CloseWindow {
+    /// Compositor id of the window
+    pub id: String,
+}
+
+impl CloseWindow {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PinClipboardEntry {
+    /// Id of the clipboard history entry
+    pub id: String,
+    /// Pinned entries are listed first and never dropped
+    pub pinned: bool,
+}
+
+impl PinClipboardEntry {
+    pub fn new(id: impl Into<String>, pinned: bool) -> Self {
+        Self {
+            id: id.into(),
+            pinned,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteClipboardEntry {
+    /// Id of the clipboard history entry
+    pub id: String,
+}
+
+impl DeleteClipboardEntry {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+/// How [`TypeText`] puts the text into the focused window
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum TypeMode {
+    /// Types the characters one by one
+    #[default]
+    Type,
+    /// Copies the text, presses Ctrl+V and puts the previous clipboard back
+    Paste,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TypeText {
+    pub text: String,
+    #[serde(default)]
+    pub mode: TypeMode,
+}
+
+impl TypeText {
+    pub fn new(text: impl Into<String>, mode: TypeMode) -> Self {
+        Self {
+            text: text.into(),
+            mode,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShowInFolder {
+    /// File or folder to show in the file manager
+    pub path: PathBuf,
+}
+
+impl ShowInFolder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CopyFile {
+    /// File or folder to copy, pasted by file managers as the file itself
+    pub path: PathBuf,
+}
+
+impl CopyFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ResultAction {
+    Core(Action),
+    Launcher(LauncherAction),
+}
+
+impl From<Action> for ResultAction {
+    fn from(action: Action) -> Self {
+        ResultAction::Core(action)
+    }
+}
+
+impl From<LauncherAction> for ResultAction {
+    fn from(action: LauncherAction) -> Self {
+        ResultAction::Launcher(action)
+    }
+}
+
+/// Alternate action of a result, listed in the action menu
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SecondaryAction {
+    pub text: String,
+    pub action: ResultAction,
+}
+
+impl SecondaryAction {
+    pub fn new(text: impl Into<String>, action: impl Into<ResultAction>) -> Self {
+        Self {
+            text: text.into(),
+            action: action.into(),
+        }
+    }
+}
+
+/// Result sent to the frontend. Same shape as [`TUIResult`] but can also carry launcher actions.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResult {
+    pub icon_path: Option<PathBuf>,
+    pub text: String,
+    pub secondary_text: Option<String>,
+    pub action: Option<ResultAction>,
+    /// Alternate actions, in the order they are listed
+    #[serde(default)]
+    pub actions: Vec<SecondaryAction>,
+    /// Short label shown next to the result, e.g. `Running`
+    #[serde(default)]
+    pub badge: Option<String>,
+    pub info: String,
+    /// Characters of the text that matched the query, as `[start, end)` char indexes
+    #[serde(default)]
+    pub highlights: Vec<(usize, usize)>,
+    /// Rank among the results of every provider, roughly 0 to 100 plus the frecency bonus
+    #[serde(skip)]
+    pub score: f64,
+}
+
+impl SearchResult {
+    pub fn new(text: impl Into<String>, info: impl Into<String>) -> Self {
+        Self {
+            icon_path: None,
+            text: text.into(),
+            secondary_text: None,
+            action: None,
+            actions: vec![],
+            badge: None,
+            info: info.into(),
+            highlights: vec![],
+            score: 0.0,
+        }
+    }
+
+    pub fn set_secondary_text(mut self, secondary_text: impl Into<String>) -> Self {
+        self.secondary_text = Some(secondary_text.into());
+        self
+    }
+
+    pub fn set_action(mut self, action: impl Into<ResultAction>) -> Self {
+        self.action = Some(action.into());
+        self
+    }
+
+    pub fn set_score(mut self, score: f64) -> Self {
+        self.score = score;
+        self
+    }
+
+    pub fn set_highlights(mut self, highlights: Vec<(usize, usize)>) -> Self {
+        self.highlights = highlights;
+        self
+    }
+
+    pub fn set_badge(mut self, badge: impl Into<String>) -> Self {
+        self.badge = Some(badge.into());
+        self
+    }
+
+    pub fn add_action(mut self, text: impl Into<String>, action: impl Into<ResultAction>) -> Self {
+        self.actions.push(SecondaryAction::new(text, action));
+        self
+    }
+
+    pub fn set_icon_path(&mut self, icon_path: impl Into<PathBuf>) {
+        self.icon_path = Some(icon_path.into());
+    }
+}
+
+impl From<TUIResult> for SearchResult {
+    fn from(result: TUIResult) -> Self {
+        Self {
+            icon_path: result.icon_path,
+            text: result.text,
+            secondary_text: result.secondary_text,
+            action: result.action.map(ResultAction::Core),
+            actions: vec![],
+            badge: None,
+            info: result.info,
+            highlights: vec![],
+            score: 0.0,
+        }
+    }
+}
@@ -1,135 +1,458 @@
-use regex::Regex;
-use sniffer_rs::sniffer::Sniffer;
+use std::{
+    error::Error,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, Window};
+use tokio::{task::JoinSet, time::timeout};
 use tuicher_rs::{
-    config::{get_config, SearchEngine},
-    result::{Action, OpenApp, OpenURL, TUIResult},
+    config::SearchEngine,
+    result::{Action, OpenURL},
     utils::Query,
 };
 
 use crate::{
-    apps::get_apps,
-    plugins::{
-        bookmarks::get_bookmarks_results, emojis::get_emoji_results, session::get_session_results,
-    },
+    apps::search::{get_app_results, get_app_with_args_results, get_open_with_results},
+    matcher::fuzzy_match,
+    result::{ResultAction, SearchResult},
+    secondary_actions::get_app_path,
+    state::AppState,
+    utils::get_typed_path,
 };
 
+/// How a query is answered
+enum SearchPlan {
+    /// Results known without asking any provider
+    Results(Vec<SearchResult>),
+    /// The provider selected by the keyword, by its index in the state, with the query left
+    Provider(usize, String),
+    /// Every global provider, merged into one list
+    Global,
+}
+
+#[derive(Serialize, Clone)]
+struct SearchPayload {
+    query_id: u64,
+    results: Vec<SearchResult>,
+    /// Whether more results of the query are still coming
+    pending: bool,
+    error: Option<String>,
+}
+
+/// Starts a search and returns right away. Results are sent through the `show-plugin-results`
+/// event as providers finish, and the search is cancelled when a newer query starts.
 #[tauri::command]
-pub fn invoke_search(text: String) -> Result<Vec<TUIResult>, String> {
-    if text.is_empty() {
-        return Ok(vec![]);
+pub async fn invoke_search(text: String, query_id: u64, window: Window) -> Result<(), String> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let task = tokio::spawn(stream_search(
+        window.clone(),
+        text,
+        query_id,
+        cancelled.clone(),
+    ));
+
+    if !window
+        .state::<AppState>()
+        .start_search(query_id, task.abort_handle(), cancelled)
+    {
+        task.abort();
     }
 
-    let config = get_config().map_err(|_| "Failed to get config")?;
-    let sniffer = Sniffer::new();
-    let search_query = Query::new(&text).map_err(|e| e.to_string())?;
-    let mut results: Vec<TUIResult> = vec![];
+    Ok(())
+}
 
-    if sniffer.matches("Settings", &search_query.full_text) {
-        results.push(
-            TUIResult::new("Settings", "settings")
-                .set_secondary_text("Open Tuicher Settings")
-                .set_action(Action::OpenSettings),
-        );
+/// Forgets the query IDs of an earlier page, called when the frontend (re)loads and starts
+/// counting from 0 again
+#[tauri::command]
+pub fn invoke_reset_search(window: Window) -> Result<(), String> {
+    let state = window.state::<AppState>();
+
+    state.reset_search();
+    state.clear_navigation();
+
+    Ok(())
+}
+
+async fn stream_search(window: Window, text: String, query_id: u64, cancelled: Arc<AtomicBool>) {
+    let plan =
+        plan_search(&window.state::<AppState>(), &text, &cancelled).map_err(|e| e.to_string());
+
+    let plan = match plan {
+        Ok(plan) => plan,
+        Err(e) => {
+            emit_results(&window, query_id, vec![], false, Some(e));
+            return;
+        }
+    };
+
+    match plan {
+        SearchPlan::Results(results) => emit_results(&window, query_id, results, false, None),
+        SearchPlan::Provider(index, query) => {
+            match query_provider(window.clone(), index, query, false, cancelled).await {
+                Ok(results) => emit_results(&window, query_id, results, false, None),
+                Err(e) => emit_results(&window, query_id, vec![], false, Some(e)),
+            }
+        }
+        SearchPlan::Global => {
+            let state = window.state::<AppState>();
+            let base_results = get_base_results(&state, &text);
+            let mut provider_results: Vec<Vec<SearchResult>> = vec![vec![]; state.providers.len()];
+            let mut tasks = JoinSet::new();
+
+            for index in get_global_providers(&state) {
+                let window = window.clone();
+                let text = text.clone();
+                let cancelled = cancelled.clone();
+
+                tasks.spawn(async move {
+                    (
+                        index,
+                        query_provider(window, index, text, true, cancelled).await,
+                    )
+                });
+            }
+
+            // What is known so far is shown while slower providers are still working
+            loop {
+                // Kept in provider order, so ties don't depend on which provider finished first
+                let results = base_results
+                    .iter()
+                    .chain(provider_results.iter().flatten())
+                    .cloned()
+                    .collect();
+
+                emit_results(
+                    &window,
+                    query_id,
+                    merge_results(&state, &text, results),
+                    !tasks.is_empty(),
+                    None,
+                );
+
+                let Some(task) = tasks.join_next().await else {
+                    break;
+                };
+
+                if let Ok((index, Ok(results))) = task {
+                    provider_results[index] = limit_results(&state, index, results);
+                }
+            }
+        }
+    }
+}
+
+/// Runs a provider on the blocking pool, giving up after its timeout. Blocking tasks can't be
+/// aborted, so the provider itself stops once it sees `cancelled`. `global` searches are the
+/// ones without the provider's keyword.
+async fn query_provider(
+    window: Window,
+    index: usize,
+    query: String,
+    global: bool,
+    cancelled: Arc<AtomicBool>,
+) -> Result<Vec<SearchResult>, String> {
+    let provider_timeout = window.state::<AppState>().providers[index].timeout();
+
+    let task = tokio::task::spawn_blocking(move || {
+        let state = window.state::<AppState>();
+
+        let provider = &state.providers[index];
+
+        if global {
+            provider.query_global(&state, &query, &cancelled)
+        } else {
+            provider.query(&state, &query, &cancelled)
+        }
+        .map_err(|e| e.to_string())
+    });
+
+    match timeout(provider_timeout, task).await {
+        Ok(Ok(results)) => results,
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!(
+            "Search took longer than {}ms",
+            provider_timeout.as_millis()
+        )),
+    }
+}
+
+/// Sends results to the window unless a newer query has replaced this one
+fn emit_results(
+    window: &Window,
+    query_id: u64,
+    results: Vec<SearchResult>,
+    pending: bool,
+    error: Option<String>,
+) {
+    if !window.state::<AppState>().is_current_search(query_id) {
+        return;
+    }
+
+    let payload = SearchPayload {
+        query_id,
+        results,
+        pending,
+        error,
+    };
+
+    let _ = window.emit("show-plugin-results", payload);
+}
+
+/// Replaces the shown results with the innermost navigation level, or with the results of an
+/// empty query at the top level. Sent as the newest query, whose remaining results are dropped.
+pub fn show_navigation_level(window: &Window) -> Result<(), Box<dyn Error>> {
+    let state = window.state::<AppState>();
+    let results = match plan_search(&state, "", &AtomicBool::new(false))? {
+        SearchPlan::Results(results) => results,
+        _ => vec![],
     };
 
+    state.cancel_search();
+    emit_results(window, state.current_search_id(), results, false, None);
+
+    Ok(())
+}
+
+/// Goes back one navigation level. Returns `false` at the top level, where there is nothing
+/// to go back to.
+#[tauri::command]
+pub fn invoke_navigate_back(window: Window) -> Result<bool, String> {
+    if !window.state::<AppState>().pop_navigation() {
+        return Ok(false);
+    }
+
+    show_navigation_level(&window).map_err(|e| e.to_string())?;
+
+    Ok(true)
+}
+
+/// Searches using only what is in memory, waiting for every provider
+pub fn search(state: &AppState, text: &str) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let cancelled = AtomicBool::new(false);
+
+    match plan_search(state, text, &cancelled)? {
+        SearchPlan::Results(results) => Ok(results),
+        SearchPlan::Provider(index, query) => {
+            state.providers[index].query(state, &query, &cancelled)
+        }
+        SearchPlan::Global => {
+            let mut results = get_base_results(state, text);
+
+            for index in get_global_providers(state) {
+                let provider_results = state.providers[index].query_global(state, text, &cancelled);
+
+                if let Ok(provider_results) = provider_results {
+                    results.extend(limit_results(state, index, provider_results));
+                }
+            }
+
+            Ok(merge_results(state, text, results))
+        }
+    }
+}
+
+fn plan_search(
+    state: &AppState,
+    text: &str,
+    cancelled: &AtomicBool,
+) -> Result<SearchPlan, Box<dyn Error>> {
+    if let Some(results) = state.navigation_results() {
+        return Ok(SearchPlan::Results(filter_navigation_results(
+            results, text,
+        )));
+    }
+
+    if text.is_empty() {
+        return Ok(SearchPlan::Results(vec![]));
+    }
+
+    if let Some((category, query)) = parse_category_filter(text) {
+        return Ok(SearchPlan::Results(get_app_results(
+            state,
+            &query,
+            Some(&category),
+            cancelled,
+        )));
+    }
+
+    if let Some(path) = get_typed_path(text) {
+        return Ok(SearchPlan::Results(get_open_with_results(state, &path)));
+    }
+
+    let search_query = Query::new(text)?;
+
     if let Some(keyword) = search_query.keyword.clone() {
-        let search_engine = config
+        let search_engine = state
+            .config()
             .search_engines
             .clone()
             .into_iter()
             .find(|engine| &engine.keyword == &keyword);
 
         if let Some(search_engine) = search_engine {
-            return Ok(vec![get_search_engine_result(
+            return Ok(SearchPlan::Results(vec![get_search_engine_result(
                 &search_engine,
                 &search_query.get_query().clone(),
-            )]);
+            )]));
         }
 
-        if config.enable_session_manager && keyword == config.session_manager_keyword {
-            return Ok(get_session_results(&sniffer, &search_query.get_query())
-                .map_err(|e| e.to_string())?);
-        }
-
-        if config.enable_emojis && keyword == config.emojis_keyword {
-            return Ok(get_emoji_results(&sniffer, &search_query.get_query()));
-        }
+        let provider = state
+            .providers
+            .iter()
+            .position(|provider| provider.keyword(state).as_ref() == Some(&keyword));
 
-        if config.enable_bookmarks && keyword == config.bookmarks_keyword {
-            return Ok(
-                get_bookmarks_results(&config, &sniffer, &search_query.get_query())
-                    .map_err(|e| e.to_string())?,
-            );
+        if let Some(index) = provider {
+            return Ok(SearchPlan::Provider(index, search_query.get_query()));
         }
     }
 
-    let url_regex = Regex::new(
-        r"https?://(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()@:%_\+.~#?&//=]*)",
-    ).map_err(|e|e.to_string())?;
+    Ok(SearchPlan::Global)
+}
 
-    let https_concat = format!("https://{}", &text);
+/// Results of a navigation level that match the query, best first. An empty query keeps all
+/// of them in their own order.
+fn filter_navigation_results(results: Vec<SearchResult>, text: &str) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = results
+        .into_iter()
+        .filter_map(|result| {
+            let found = fuzzy_match(&result.text, text)?;
+            Some(result.set_score(found.score).set_highlights(found.ranges))
+        })
+        .collect();
 
-    if url_regex.is_match(&https_concat) {
-        return Ok(vec![TUIResult::new("Open", "url-search")
-            .set_secondary_text(&https_concat)
-            .set_action(Action::OpenURL(OpenURL::new(&https_concat)))]);
-    }
+    // Stable, so equally scored results keep the order the plugin gave them
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results
+}
 
-    let mut apps: Vec<TUIResult> = get_apps()
-        .unwrap_or(vec![])
-        .into_iter()
-        .filter_map(|app| {
-            let matches_keywords = app
-                .clone()
-                .keywords
-                .iter()
-                .any(|key| sniffer.matches(key, &text));
+/// Indexes of the enabled providers that take part in searches without a keyword
+fn get_global_providers(state: &AppState) -> Vec<usize> {
+    (0..state.providers.len())
+        .filter(|index| {
+            let provider = &state.providers[*index];
+            provider.is_global() && provider.keyword(state).is_some()
+        })
+        .collect()
+}
+
+/// Results of searches without a keyword that don't come from a provider
+fn get_base_results(state: &AppState, text: &str) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = vec![];
+
+    if let Some(found) = fuzzy_match("Settings", text) {
+        results.push(
+            SearchResult::new("Settings", "settings")
+                .set_secondary_text("Open Tuicher Settings")
+                .set_action(Action::OpenSettings)
+                .set_score(found.score)
+                .set_highlights(found.ranges),
+        );
+    };
 
-            if sniffer.matches(&app.name, &text) || matches_keywords {
-                let action = Action::OpenApp(OpenApp::new(&app.path));
+    results.append(&mut get_app_with_args_results(state, text));
 
-                let mut result = TUIResult::new(&app.name, &"app".to_string())
-                    .set_secondary_text(if let Some(description) = app.description {
-                        description
-                    } else {
-                        "Application".to_string()
-                    })
-                    .set_action(action);
+    let https_concat = format!("https://{}", text);
 
-                if let Some(icon_path) = app.icon_path {
-                    result.set_icon_path(icon_path);
-                }
+    if state.matchers.url_regex.is_match(&https_concat) {
+        results.push(
+            SearchResult::new("Open", "url-search")
+                .set_secondary_text(&https_concat)
+                .set_action(Action::OpenURL(OpenURL::new(&https_concat)))
+                .set_score(90.0),
+        );
+    }
 
-                return Some(result);
-            }
+    results
+}
 
-            None
-        })
-        .collect();
+/// The best results of a provider, at most its `max_results`
+fn limit_results(
+    state: &AppState,
+    index: usize,
+    mut results: Vec<SearchResult>,
+) -> Vec<SearchResult> {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(state.providers[index].max_results(state));
+    results
+}
 
-    if !apps.is_empty() {
-        results.append(&mut apps);
-        return Ok(results);
+/// Merges results into one list ranked by score, where duplicates keep the better scored
+/// result. The default search engine always comes last.
+fn merge_results(state: &AppState, text: &str, results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut merged: Vec<SearchResult> = vec![];
+
+    for result in results {
+        let key = get_dedup_key(&result);
+        let duplicate = key.as_ref().and_then(|key| {
+            merged
+                .iter()
+                .position(|other| get_dedup_key(other).as_ref() == Some(key))
+        });
+
+        match duplicate {
+            Some(index) if merged[index].score < result.score => merged[index] = result,
+            Some(_) => {}
+            None => merged.push(result),
+        }
     }
 
+    // Stable, so equally scored results keep the provider order
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let config = state.config();
     let default_search_engine = config
-        .clone()
         .search_engines
-        .into_iter()
+        .iter()
         .find(|search_engine| search_engine.id == config.default_search_engine);
 
     if let Some(search_engine) = default_search_engine {
-        return Ok(vec![get_search_engine_result(&search_engine, &text)]);
+        merged.push(get_search_engine_result(search_engine, text));
+    }
+
+    merged
+}
+
+/// What makes two results the same: the url they open or the app they launch
+fn get_dedup_key(result: &SearchResult) -> Option<String> {
+    if let Some(path) = get_app_path(result) {
+        return Some(path.display().to_string());
+    }
+
+    match result.action.as_ref()? {
+        ResultAction::Core(Action::OpenURL(open_url)) => Some(normalize_url(&open_url.url)),
+        _ => None,
     }
+}
+
+/// `https://www.example.com/` and `example.com` are the same page
+fn normalize_url(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.strip_prefix("www.").unwrap_or(url);
 
-    Ok(vec![])
+    url.trim_end_matches('/').to_lowercase()
 }
 
-fn get_search_engine_result(search_engine: &SearchEngine, text: &str) -> TUIResult {
+fn get_search_engine_result(search_engine: &SearchEngine, text: &str) -> SearchResult {
     let search_query = search_engine.url.clone().replace("%s", text);
     let action = Action::OpenURL(OpenURL::new(search_query));
 
-    TUIResult::new(&search_engine.name, &"search-engine".to_string())
+    SearchResult::new(&search_engine.name, "search-engine")
         .set_secondary_text(format!("Search for {}", &text))
         .set_action(action)
 }
+
+/// Splits `cat:<category>` out of the query, e.g. `cat:graphics gimp`
+fn parse_category_filter(text: &str) -> Option<(String, String)> {
+    let mut category: Option<String> = None;
+    let mut query_parts: Vec<&str> = vec![];
+
+    for part in text.split_whitespace() {
+        match part.strip_prefix("cat:") {
+            Some(value) if !value.is_empty() => category = Some(value.to_string()),
+            _ => query_parts.push(part),
+        }
+    }
+
+    Some((category?, query_parts.join(" ")))
+}
@@ -0,0 +1,192 @@
+//! Alternate actions of a result, listed when the action menu is opened. Results can carry
+//! their own, e.g. launch profiles, and the rest are derived from the main action so searching
+//! doesn't have to build them for every result.
+
+use std::path::{Path, PathBuf};
+
+use tauri::State;
+use tuicher_rs::result::{Action, CopyText};
+
+use crate::{
+    apps::{search::get_file_handlers, App},
+    launcher::exec::{get_target_kind, parse_exec},
+    mime::{get_associated_ids, get_file_mime_type, MimeApps},
+    result::{
+        CopyFile, LauncherAction, OpenDesktopAction, OpenWith, ResultAction, SearchResult,
+        SecondaryAction, ShowInFolder, TypeMode, TypeText,
+    },
+    state::AppState,
+};
+
+#[tauri::command]
+pub fn invoke_get_result_actions(
+    result: SearchResult,
+    state: State<AppState>,
+) -> Result<Vec<SecondaryAction>, String> {
+    Ok(get_result_actions(&state, &result))
+}
+
+/// The alternate actions of a result in the order they are listed, the result's own first
+pub fn get_result_actions(state: &AppState, result: &SearchResult) -> Vec<SecondaryAction> {
+    let mut actions = result.actions.clone();
+
+    if let Some(path) = get_app_path(result) {
+        actions.extend(get_app_actions(&path));
+        return actions;
+    }
+
+    let derived_actions = match &result.action {
+        Some(ResultAction::Core(Action::OpenURL(open_url))) => {
+            get_url_actions(state, &open_url.url)
+        }
+        Some(ResultAction::Core(Action::CopyText(copy_text))) => {
+            let mut actions = get_text_actions(&copy_text.text);
+
+            if result.info == "emojis" {
+                actions.extend(get_emoji_actions(&copy_text.text));
+            }
+
+            actions
+        }
+        Some(ResultAction::Core(Action::OpenFile(open_file))) => {
+            let path = Path::new(&open_file.path);
+
+            let mut actions = get_open_with_actions(state, path);
+            actions.extend(get_file_actions(path));
+            actions
+        }
+        Some(ResultAction::Launcher(LauncherAction::OpenWith(open_with))) => {
+            get_file_actions(&open_with.file)
+        }
+        _ => vec![],
+    };
+
+    actions.extend(derived_actions);
+    actions
+}
+
+/// Desktop file of an app result. Running apps are focused by their main action, so their
+/// launch action is looked up in the secondary ones too.
+pub fn get_app_path(result: &SearchResult) -> Option<PathBuf> {
+    result
+        .action
+        .iter()
+        .chain(result.actions.iter().map(|action| &action.action))
+        .find_map(|action| match action {
+            ResultAction::Core(Action::OpenApp(open_app)) => Some(open_app.path.clone()),
+            ResultAction::Launcher(LauncherAction::OpenAppWithProfile(open_app)) => {
+                Some(open_app.path.clone())
+            }
+            _ => None,
+        })
+}
+
+fn get_app_actions(path: &Path) -> Vec<SecondaryAction> {
+    vec![
+        SecondaryAction::new(
+            "Copy Desktop File Path",
+            Action::CopyText(CopyText::new(&path.display().to_string())),
+        ),
+        SecondaryAction::new(
+            "Open Containing Folder",
+            LauncherAction::ShowInFolder(ShowInFolder::new(path)),
+        ),
+    ]
+}
+
+fn get_file_actions(path: &Path) -> Vec<SecondaryAction> {
+    vec![
+        SecondaryAction::new(
+            "Show in Folder",
+            LauncherAction::ShowInFolder(ShowInFolder::new(path)),
+        ),
+        SecondaryAction::new("Copy File", LauncherAction::CopyFile(CopyFile::new(path))),
+        SecondaryAction::new(
+            "Copy Path",
+            Action::CopyText(CopyText::new(&path.display().to_string())),
+        ),
+    ]
+}
+
+/// "Open With" every app that opens the file
+fn get_open_with_actions(state: &AppState, path: &Path) -> Vec<SecondaryAction> {
+    let mime_type = get_file_mime_type(&state.matchers.mime_globs, path);
+    let apps = state.apps();
+
+    get_file_handlers(&apps, &MimeApps::load(), &mime_type)
+        .into_iter()
+        .map(|app| {
+            SecondaryAction::new(
+                format!("Open With {}", &app.name),
+                LauncherAction::OpenWith(OpenWith::new(&app.path, path)),
+            )
+        })
+        .collect()
+}
+
+fn get_url_actions(state: &AppState, url: &str) -> Vec<SecondaryAction> {
+    let mut actions = vec![SecondaryAction::new(
+        "Copy URL",
+        Action::CopyText(CopyText::new(url)),
+    )];
+
+    if let Some(action) = get_private_window_action(&state.apps(), url) {
+        actions.push(SecondaryAction::new("Open in Private Window", action));
+    }
+
+    actions
+}
+
+/// Opens the url with the private window action of the default browser. Only actions whose
+/// `Exec` takes a url are used, otherwise the url would be dropped.
+fn get_private_window_action(apps: &[App], url: &str) -> Option<LauncherAction> {
+    let mime_apps = MimeApps::load();
+
+    let browser = get_associated_ids("x-scheme-handler/https", &mime_apps)
+        .iter()
+        .find_map(|id| apps.iter().find(|app| &app.id == id))?;
+
+    let private_action = browser.actions.iter().find(|action| {
+        let id = action.id.to_lowercase();
+
+        let takes_url = action
+            .exec
+            .as_deref()
+            .and_then(|exec| parse_exec(exec).ok())
+            .and_then(|exec| get_target_kind(&exec))
+            .is_some();
+
+        (id.contains("private") || id.contains("incognito")) && takes_url
+    })?;
+
+    Some(LauncherAction::OpenDesktopAction(
+        OpenDesktopAction::new(&browser.path, &private_action.id)
+            .set_targets(vec![url.to_string()]),
+    ))
+}
+
+/// Puts copied text into the window that was focused before the launcher instead
+fn get_text_actions(text: &str) -> Vec<SecondaryAction> {
+    vec![
+        SecondaryAction::new(
+            "Type Text",
+            LauncherAction::TypeText(TypeText::new(text, TypeMode::Type)),
+        ),
+        SecondaryAction::new(
+            "Paste Text",
+            LauncherAction::TypeText(TypeText::new(text, TypeMode::Paste)),
+        ),
+    ]
+}
+
+fn get_emoji_actions(text: &str) -> Vec<SecondaryAction> {
+    emojis::get(text)
+        .and_then(|emoji| emoji.shortcode())
+        .map(|shortcode| {
+            vec![SecondaryAction::new(
+                "Copy Shortcode",
+                Action::CopyText(CopyText::new(&format!(":{shortcode}:"))),
+            )]
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,279 @@
+use std::{
+    error::Error,
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::channel,
+        Arc, Mutex, RwLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use regex::Regex;
+use tauri::{AppHandle, Manager};
+use tokio::task::AbortHandle;
+use tuicher_rs::config::{get_config, Config};
+
+use crate::{
+    apps::{get_apps, running::RunningApps, App},
+    config::{get_launcher_config, LauncherConfig},
+    history::{get_launch_history, LaunchHistory},
+    mime::MimeGlobs,
+    plugins::{
+        clipboard::{get_clipboard_history, ClipboardHistory},
+        get_providers, SearchProvider,
+    },
+    result::SearchResult,
+};
+
+/// How long the running apps are reused before `/proc` and the compositor are asked again
+const RUNNING_APPS_TTL: Duration = Duration::from_secs(2);
+
+const URL_PATTERN: &str = r"https?://(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()@:%_\+.~#?&//=]*)";
+
+/// Everything a search needs, kept in memory so typing doesn't touch the disk. The indexer,
+/// the config watcher and the launch history replace their part in place.
+pub struct AppState {
+    apps: RwLock<Arc<Vec<App>>>,
+    config: RwLock<Arc<Config>>,
+    launcher_config: RwLock<Arc<LauncherConfig>>,
+    history: RwLock<Arc<LaunchHistory>>,
+    clipboard_history: RwLock<Arc<ClipboardHistory>>,
+    /// Running apps and when they were loaded, with no load time when they were set instead
+    running_apps: RwLock<Option<(Option<Instant>, Arc<RunningApps>)>>,
+    /// Query ID of the newest search, its task and the flag its providers check, cancelled
+    /// when a newer query starts
+    search: Mutex<Option<(u64, AbortHandle, Arc<AtomicBool>)>>,
+    /// Results opened by `ShowResults` actions, the innermost last. Searches only look in the
+    /// innermost level while there is one.
+    navigation: Mutex<Vec<Vec<SearchResult>>>,
+    pub matchers: Matchers,
+    pub providers: Vec<Box<dyn SearchProvider>>,
+}
+
+/// Matchers that are expensive to build, so they are built once
+pub struct Matchers {
+    pub url_regex: Regex,
+    pub mime_globs: MimeGlobs,
+}
+
+impl AppState {
+    pub fn new(
+        apps: Vec<App>,
+        config: Config,
+        launcher_config: LauncherConfig,
+        history: LaunchHistory,
+        mime_globs: MimeGlobs,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            apps: RwLock::new(Arc::new(apps)),
+            config: RwLock::new(Arc::new(config)),
+            launcher_config: RwLock::new(Arc::new(launcher_config)),
+            history: RwLock::new(Arc::new(history)),
+            clipboard_history: RwLock::new(Arc::new(ClipboardHistory::default())),
+            running_apps: RwLock::new(None),
+            search: Mutex::new(None),
+            navigation: Mutex::new(vec![]),
+            matchers: Matchers {
+                url_regex: Regex::new(URL_PATTERN)?,
+                mime_globs,
+            },
+            providers: get_providers(),
+        })
+    }
+
+    /// Loads the state from the last written index and config
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let state = Self::new(
+            get_apps().unwrap_or_default(),
+            get_config()?,
+            get_launcher_config().unwrap_or_default(),
+            get_launch_history().unwrap_or_default(),
+            MimeGlobs::load(),
+        )?;
+
+        state.set_clipboard_history(get_clipboard_history().unwrap_or_default());
+
+        Ok(state)
+    }
+
+    pub fn apps(&self) -> Arc<Vec<App>> {
+        self.apps.read().unwrap().clone()
+    }
+
+    pub fn set_apps(&self, apps: Vec<App>) {
+        *self.apps.write().unwrap() = Arc::new(apps);
+    }
+
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: Config) {
+        *self.config.write().unwrap() = Arc::new(config);
+    }
+
+    pub fn launcher_config(&self) -> Arc<LauncherConfig> {
+        self.launcher_config.read().unwrap().clone()
+    }
+
+    pub fn set_launcher_config(&self, launcher_config: LauncherConfig) {
+        *self.launcher_config.write().unwrap() = Arc::new(launcher_config);
+    }
+
+    pub fn history(&self) -> Arc<LaunchHistory> {
+        self.history.read().unwrap().clone()
+    }
+
+    pub fn set_history(&self, history: LaunchHistory) {
+        *self.history.write().unwrap() = Arc::new(history);
+    }
+
+    pub fn clipboard_history(&self) -> Arc<ClipboardHistory> {
+        self.clipboard_history.read().unwrap().clone()
+    }
+
+    pub fn set_clipboard_history(&self, clipboard_history: ClipboardHistory) {
+        *self.clipboard_history.write().unwrap() = Arc::new(clipboard_history);
+    }
+
+    /// Running apps, loaded again when the last load is older than [`RUNNING_APPS_TTL`]
+    pub fn running_apps(&self) -> Arc<RunningApps> {
+        if let Some((loaded_at, running_apps)) = self.running_apps.read().unwrap().as_ref() {
+            if loaded_at.is_none_or(|loaded_at| loaded_at.elapsed() < RUNNING_APPS_TTL) {
+                return running_apps.clone();
+            }
+        }
+
+        let running_apps = Arc::new(RunningApps::load());
+        *self.running_apps.write().unwrap() = Some((Some(Instant::now()), running_apps.clone()));
+
+        running_apps
+    }
+
+    /// Uses these running apps from now on instead of looking them up
+    pub fn set_running_apps(&self, running_apps: RunningApps) {
+        *self.running_apps.write().unwrap() = Some((None, Arc::new(running_apps)));
+    }
+
+    /// Makes `query_id` the current search and cancels the one it supersedes. Returns `false`
+    /// when a newer search already started, in which case `task` should be cancelled instead.
+    pub fn start_search(
+        &self,
+        query_id: u64,
+        task: AbortHandle,
+        cancelled: Arc<AtomicBool>,
+    ) -> bool {
+        let mut search = self.search.lock().unwrap();
+
+        if let Some((current_id, current_task, current_cancelled)) = search.as_ref() {
+            if *current_id > query_id {
+                return false;
+            }
+
+            current_task.abort();
+            current_cancelled.store(true, Ordering::Relaxed);
+        }
+
+        *search = Some((query_id, task, cancelled));
+        true
+    }
+
+    /// Query ID of the newest search, 0 before the first one
+    pub fn current_search_id(&self) -> u64 {
+        self.search
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |(query_id, _, _)| *query_id)
+    }
+
+    /// Stops the newest search from sending more results, keeping its query ID current
+    pub fn cancel_search(&self) {
+        if let Some((_, task, cancelled)) = self.search.lock().unwrap().as_ref() {
+            task.abort();
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Cancels the newest search and forgets its query ID, for a frontend that starts counting
+    /// again from 0
+    pub fn reset_search(&self) {
+        self.cancel_search();
+        *self.search.lock().unwrap() = None;
+    }
+
+    /// Whether no newer search has started. A search can finish before it is registered.
+    pub fn is_current_search(&self, query_id: u64) -> bool {
+        self.search
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|(current_id, _, _)| *current_id <= query_id)
+    }
+
+    /// Results of the innermost navigation level, `None` at the top level
+    pub fn navigation_results(&self) -> Option<Vec<SearchResult>> {
+        self.navigation.lock().unwrap().last().cloned()
+    }
+
+    pub fn push_navigation(&self, results: Vec<SearchResult>) {
+        self.navigation.lock().unwrap().push(results);
+    }
+
+    /// Goes back one navigation level. Returns `false` when already at the top level.
+    pub fn pop_navigation(&self) -> bool {
+        self.navigation.lock().unwrap().pop().is_some()
+    }
+
+    pub fn clear_navigation(&self) {
+        self.navigation.lock().unwrap().clear();
+    }
+}
+
+/// Reloads both configs into the state whenever a file in the tuicher config dir changes
+pub fn setup_config_watcher(app: AppHandle) -> Result<(), Box<dyn Error>> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| "Failed to get config dir")?
+        .join("tuicher");
+
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)?;
+    }
+
+    thread::spawn(move || {
+        let (tx, rx) = channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(tx).expect("Failed to get watcher");
+
+        watcher
+            .watch(&config_dir, RecursiveMode::NonRecursive)
+            .expect("Failed to add config dir to watcher");
+
+        for res in rx {
+            let Ok(event) = res else {
+                continue;
+            };
+
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                continue;
+            }
+
+            let state = app.state::<AppState>();
+
+            if let Ok(config) = get_config() {
+                state.set_config(config);
+            }
+
+            if let Ok(launcher_config) = get_launcher_config() {
+                state.set_launcher_config(launcher_config);
+            }
+        }
+    });
+
+    Ok(())
+}
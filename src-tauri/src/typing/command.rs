@@ -0,0 +1,46 @@
+//! Typing command line tools. `wtype` is the only way to type on Wayland without speaking the
+//! virtual-keyboard protocol ourselves, `xdotool` is used when XTest fails.
+
+use std::{
+    error::Error,
+    process::{Command, Stdio},
+};
+
+use crate::typing::Input;
+
+pub fn wtype(input: &Input) -> Result<(), Box<dyn Error>> {
+    let mut command = Command::new("wtype");
+
+    match input {
+        Input::Text(text) => command.args(["--", *text]),
+        Input::Paste => command.args(["-M", "ctrl", "v", "-m", "ctrl"]),
+    };
+
+    run(command)
+}
+
+pub fn xdotool(input: &Input) -> Result<(), Box<dyn Error>> {
+    let mut command = Command::new("xdotool");
+
+    match input {
+        Input::Text(text) => command.args(["type", "--clearmodifiers", "--", *text]),
+        Input::Paste => command.args(["key", "--clearmodifiers", "ctrl+v"]),
+    };
+
+    run(command)
+}
+
+fn run(mut command: Command) -> Result<(), Box<dyn Error>> {
+    let output = command.stdin(Stdio::null()).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(())
+}
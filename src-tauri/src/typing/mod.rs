@@ -0,0 +1,104 @@
+//! Typing text into the focused window. Wayland clients can only type through the
+//! virtual-keyboard protocol, which `wtype` speaks. On X11 XTest is used directly and `xdotool`
+//! only when that fails.
+
+use std::{
+    env,
+    error::Error,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::clipboard::{copy_text, get_clipboard_offers, set_clipboard};
+
+pub mod command;
+pub mod x11;
+
+/// How long the pasted text stays on the clipboard, so the focused app has read it before the
+/// previous clipboard is put back
+const RESTORE_DELAY: Duration = Duration::from_millis(300);
+
+/// What is sent to the focused window
+#[derive(Debug, Clone, Copy)]
+pub enum Input<'a> {
+    Text(&'a str),
+    /// Ctrl+V
+    Paste,
+}
+
+type Backend = fn(&Input) -> Result<(), Box<dyn Error>>;
+
+/// Set while [`paste_text`] has the clipboard, so neither the pasted text nor the restored
+/// clipboard end up in the clipboard history
+static PASTING: AtomicBool = AtomicBool::new(false);
+
+/// Clears [`PASTING`] however the paste ends
+struct PastingGuard;
+
+impl Drop for PastingGuard {
+    fn drop(&mut self) {
+        PASTING.store(false, Ordering::SeqCst);
+    }
+}
+
+pub fn type_text(text: &str) -> Result<(), Box<dyn Error>> {
+    send_input(&Input::Text(text))
+}
+
+/// Copies the text and presses Ctrl+V, then puts back what was on the clipboard before
+pub fn paste_text(text: &str) -> Result<(), Box<dyn Error>> {
+    PASTING.store(true, Ordering::SeqCst);
+    let _guard = PastingGuard;
+
+    let previous = get_clipboard_offers().unwrap_or_default();
+
+    copy_text(text)?;
+    send_input(&Input::Paste)?;
+
+    thread::sleep(RESTORE_DELAY);
+
+    if !previous.is_empty() {
+        set_clipboard(&previous)?;
+
+        // The history watcher hears about the restored clipboard a little later
+        thread::sleep(RESTORE_DELAY);
+    }
+
+    Ok(())
+}
+
+/// Whether a paste is using the clipboard right now
+pub fn is_pasting() -> bool {
+    PASTING.load(Ordering::SeqCst)
+}
+
+/// Sends the input with every backend of the session in order until one works. The error lists
+/// why each of them failed.
+fn send_input(input: &Input) -> Result<(), Box<dyn Error>> {
+    let mut backends: Vec<(&str, Backend)> = vec![];
+
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        backends.push(("wtype", command::wtype));
+    }
+
+    if env::var_os("DISPLAY").is_some() {
+        backends.push(("XTest", x11::send));
+        backends.push(("xdotool", command::xdotool));
+    }
+
+    if backends.is_empty() {
+        return Err("No Wayland or X11 display to type into".into());
+    }
+
+    let mut errors: Vec<String> = vec![];
+
+    for (name, backend) in backends {
+        match backend(input) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{name}: {e}")),
+        }
+    }
+
+    Err(format!("Failed to type ({})", errors.join(", ")).into())
+}
@@ -0,0 +1,178 @@
+use std::{error::Error, thread, time::Duration};
+
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xproto::{ConnectionExt as _, Keycode, Keysym, Window, KEY_PRESS_EVENT, KEY_RELEASE_EVENT},
+        xtest::ConnectionExt as _,
+    },
+    rust_connection::RustConnection,
+    CURRENT_TIME,
+};
+
+use crate::typing::Input;
+
+/// Time for clients to handle a key or a keymap change before the next one
+const KEY_DELAY: Duration = Duration::from_millis(12);
+
+const XK_RETURN: Keysym = 0xff0d;
+const XK_TAB: Keysym = 0xff09;
+const XK_CONTROL_L: Keysym = 0xffe3;
+const XK_V: Keysym = 0x0076;
+
+/// Keymap of the server, as loaded when connecting
+struct Keyboard {
+    connection: RustConnection,
+    root: Window,
+    min_keycode: Keycode,
+    keysyms_per_keycode: u8,
+    /// Keysyms of every keycode from `min_keycode` on, `keysyms_per_keycode` each
+    keysyms: Vec<Keysym>,
+}
+
+/// Fakes key events with XTest. Characters without a key of their own are typed by mapping
+/// them to a spare keycode for a moment, like `xdotool` does.
+pub fn send(input: &Input) -> Result<(), Box<dyn Error>> {
+    let keyboard = Keyboard::connect()?;
+
+    match input {
+        Input::Text(text) => keyboard.type_text(text),
+        Input::Paste => keyboard.paste(),
+    }
+}
+
+impl Keyboard {
+    fn connect() -> Result<Self, Box<dyn Error>> {
+        let (connection, screen_number) = x11rb::connect(None)?;
+
+        connection.xtest_get_version(2, 2)?.reply()?;
+
+        let setup = connection.setup();
+        let root = setup.roots[screen_number].root;
+        let min_keycode = setup.min_keycode;
+        let count = setup.max_keycode - min_keycode + 1;
+
+        let mapping = connection
+            .get_keyboard_mapping(min_keycode, count)?
+            .reply()?;
+
+        Ok(Self {
+            connection,
+            root,
+            min_keycode,
+            keysyms_per_keycode: mapping.keysyms_per_keycode,
+            keysyms: mapping.keysyms,
+        })
+    }
+
+    fn type_text(&self, text: &str) -> Result<(), Box<dyn Error>> {
+        let spare = self
+            .find_spare_keycode()
+            .ok_or_else(|| "No spare keycode to type with")?;
+
+        let typed = text.chars().try_for_each(|c| self.type_char(c, spare));
+
+        // The keymap is put back even when typing failed halfway
+        self.remap(spare, 0)?;
+
+        typed
+    }
+
+    fn type_char(&self, c: char, spare: Keycode) -> Result<(), Box<dyn Error>> {
+        let keysym = get_keysym(c);
+
+        let keycode = match self.find_keycode(keysym) {
+            Some(keycode) => keycode,
+            None => {
+                self.remap(spare, keysym)?;
+                spare
+            }
+        };
+
+        self.tap(keycode)
+    }
+
+    fn paste(&self) -> Result<(), Box<dyn Error>> {
+        let control = self
+            .find_keycode(XK_CONTROL_L)
+            .ok_or_else(|| "No Control key in the keymap")?;
+
+        let v = self
+            .find_keycode(XK_V)
+            .ok_or_else(|| "No V key in the keymap")?;
+
+        self.send_key(control, true)?;
+        self.tap(v)?;
+        self.send_key(control, false)?;
+
+        self.sync()
+    }
+
+    /// Keycode that gives the keysym without any modifier
+    fn find_keycode(&self, keysym: Keysym) -> Option<Keycode> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode as usize)
+            .position(|keysyms| keysyms.first() == Some(&keysym))
+            .map(|index| self.min_keycode + index as u8)
+    }
+
+    /// Keycode without any keysym. The last one is taken, since keyboards use the low ones.
+    fn find_spare_keycode(&self) -> Option<Keycode> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode as usize)
+            .rposition(|keysyms| keysyms.iter().all(|keysym| *keysym == 0))
+            .map(|index| self.min_keycode + index as u8)
+    }
+
+    /// Makes every column of the keycode give the keysym, so held modifiers don't change it
+    fn remap(&self, keycode: Keycode, keysym: Keysym) -> Result<(), Box<dyn Error>> {
+        let keysyms = vec![keysym; self.keysyms_per_keycode as usize];
+
+        self.connection
+            .change_keyboard_mapping(1, keycode, self.keysyms_per_keycode, &keysyms)?;
+
+        self.sync()?;
+        thread::sleep(KEY_DELAY);
+
+        Ok(())
+    }
+
+    fn tap(&self, keycode: Keycode) -> Result<(), Box<dyn Error>> {
+        self.send_key(keycode, true)?;
+        self.send_key(keycode, false)?;
+
+        self.sync()?;
+        thread::sleep(KEY_DELAY);
+
+        Ok(())
+    }
+
+    fn send_key(&self, keycode: Keycode, pressed: bool) -> Result<(), Box<dyn Error>> {
+        let event_type = if pressed {
+            KEY_PRESS_EVENT
+        } else {
+            KEY_RELEASE_EVENT
+        };
+
+        self.connection
+            .xtest_fake_input(event_type, keycode, CURRENT_TIME, self.root, 0, 0, 0)?;
+
+        Ok(())
+    }
+
+    /// Waits for the server to handle everything sent so far
+    fn sync(&self) -> Result<(), Box<dyn Error>> {
+        self.connection.get_input_focus()?.reply()?;
+        Ok(())
+    }
+}
+
+/// Latin-1 characters are their own keysym, the rest of Unicode is offset by `0x01000000`
+fn get_keysym(c: char) -> Keysym {
+    match c {
+        '\n' => XK_RETURN,
+        '\t' => XK_TAB,
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as Keysym,
+        _ => 0x0100_0000 + c as Keysym,
+    }
+}
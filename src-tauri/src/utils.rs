@@ -0,0 +1,116 @@
+use std::{
+    env,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+/// Expands a leading `~` and `$VAR` or `${VAR}` in a path typed by the user. Unknown
+/// variables are left as they are.
+pub fn expand_path(text: &str) -> PathBuf {
+    let text = text.trim();
+
+    let text = match (text.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{rest}", home.display())
+        }
+        _ => text.to_string(),
+    };
+
+    let mut expanded = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if char != '$' {
+            expanded.push(char);
+            continue;
+        }
+
+        let braced = chars.peek() == Some(&'{');
+
+        if braced {
+            chars.next();
+        }
+
+        let mut name = String::new();
+
+        while let Some(next) = chars.peek() {
+            if next.is_ascii_alphanumeric() || *next == '_' {
+                name.push(*next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if braced && chars.peek() == Some(&'}') {
+            chars.next();
+        }
+
+        match env::var(&name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ if braced => expanded.push_str(&format!("${{{name}}}")),
+            _ => expanded.push_str(&format!("${name}")),
+        }
+    }
+
+    PathBuf::from(expanded)
+}
+
+/// A path typed in the search that exists on disk
+pub fn get_typed_path(text: &str) -> Option<PathBuf> {
+    let text = text.trim();
+
+    if !(text.starts_with('/') || text.starts_with('~') || text.starts_with('$')) {
+        return None;
+    }
+
+    let path = expand_path(text);
+
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// `file://` URI of an absolute path, with everything but unreserved characters escaped
+pub fn get_file_uri(path: &Path) -> String {
+    let mut uri = "file://".to_string();
+
+    for byte in path.display().to_string().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    uri
+}
+
+/// Writes through a temporary file that is renamed over the old one, so a crash never leaves
+/// half a file. Only the user can read it, since history files can hold secrets.
+pub fn write_private_file(path: &Path, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+
+    // A file left by a crash would keep its own permissions
+    let _ = fs::remove_file(&temp_path);
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&temp_path)?;
+
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use tuicher_lib::apps::resolve::{get_desktop_file_id, resolve_desktop_files, resolve_entries};
+
+fn fixture_dirs() -> Vec<PathBuf> {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/applications");
+
+    vec![fixtures.join("home"), fixtures.join("system")]
+}
+
+fn shown_entries(desktops: &[&str]) -> HashMap<String, PathBuf> {
+    let desktops: Vec<String> = desktops.iter().map(|desktop| desktop.to_string()).collect();
+    let locales = vec!["en".to_string()];
+
+    resolve_entries(&fixture_dirs(), &desktops, &locales)
+        .into_iter()
+        .map(|(id, entry)| (id, entry.path.clone()))
+        .collect()
+}
+
+#[test]
+fn user_entry_overrides_system_entry() {
+    let entries = shown_entries(&["GNOME"]);
+
+    assert_eq!(
+        entries.get("firefox.desktop"),
+        Some(&fixture_dirs()[0].join("firefox.desktop"))
+    );
+}
+
+#[test]
+fn hidden_entry_hides_lower_precedence_entries() {
+    let files = resolve_desktop_files(&fixture_dirs());
+
+    assert_eq!(
+        files.get("removed.desktop"),
+        Some(&fixture_dirs()[0].join("removed.desktop"))
+    );
+    assert!(!shown_entries(&["GNOME"]).contains_key("removed.desktop"));
+}
+
+#[test]
+fn subdirectories_are_part_of_the_id() {
+    let dirs = fixture_dirs();
+    let path = dirs[1].join("kde/konsole.desktop");
+
+    assert_eq!(
+        get_desktop_file_id(&path, &dirs),
+        Some("kde-konsole.desktop".to_string())
+    );
+}
+
+#[test]
+fn only_show_in_is_matched_against_current_desktops() {
+    assert!(shown_entries(&["KDE"]).contains_key("kde-konsole.desktop"));
+    assert!(!shown_entries(&["GNOME"]).contains_key("kde-konsole.desktop"));
+    assert!(shown_entries(&["ubuntu", "GNOME"]).contains_key("gnome-only.desktop"));
+    assert!(!shown_entries(&[]).contains_key("gnome-only.desktop"));
+}
+
+#[test]
+fn not_show_in_is_matched_against_current_desktops() {
+    assert!(!shown_entries(&["GNOME"]).contains_key("not-in-gnome.desktop"));
+    assert!(!shown_entries(&["Unity"]).contains_key("not-in-gnome.desktop"));
+    assert!(shown_entries(&["KDE"]).contains_key("not-in-gnome.desktop"));
+}
+
+#[test]
+fn try_exec_must_be_installed() {
+    let entries = shown_entries(&["GNOME"]);
+
+    assert!(entries.contains_key("installed.desktop"));
+    assert!(!entries.contains_key("missing-binary.desktop"));
+}
+
+#[test]
+fn no_display_and_non_application_entries_are_skipped() {
+    let entries = shown_entries(&["GNOME"]);
+
+    assert!(!entries.contains_key("no-display.desktop"));
+    assert!(!entries.contains_key("link.desktop"));
+}
@@ -0,0 +1,238 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    os::unix::net::UnixListener,
+    path::PathBuf,
+    process,
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use sniffer_rs::sniffer::Sniffer;
+use tuicher_lib::{
+    compositor::{Compositor, CompositorWindow},
+    plugins::windows::get_windows_results,
+};
+
+fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ipc");
+    fs::read_to_string(path.join(name)).expect("Failed to read fixture")
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("tuicher-{}-{name}.sock", process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Fake Hyprland socket answering each connection with the next reply. Requests are sent back
+/// through the receiver.
+fn fake_hyprland(name: &str, replies: Vec<String>) -> (Compositor, Receiver<String>) {
+    let path = socket_path(name);
+    let listener = UnixListener::bind(&path).expect("Failed to bind socket");
+    let (sender, receiver) = channel();
+
+    thread::spawn(move || {
+        for reply in replies {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut request = [0u8; 1024];
+            let length = stream.read(&mut request).unwrap();
+            sender
+                .send(String::from_utf8_lossy(&request[..length]).to_string())
+                .unwrap();
+
+            stream.write_all(reply.as_bytes()).unwrap();
+        }
+    });
+
+    (Compositor::Hyprland(path), receiver)
+}
+
+/// Fake sway socket speaking the i3 IPC framing. Requests are sent back as (type, payload).
+fn fake_sway(name: &str, replies: Vec<String>) -> (Compositor, Receiver<(u32, String)>) {
+    let path = socket_path(name);
+    let listener = UnixListener::bind(&path).expect("Failed to bind socket");
+    let (sender, receiver) = channel();
+
+    thread::spawn(move || {
+        for reply in replies {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut header = [0u8; 14];
+            stream.read_exact(&mut header).unwrap();
+            assert_eq!(&header[..6], b"i3-ipc");
+
+            let length = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+            let message_type = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+
+            let mut payload = vec![0u8; length];
+            stream.read_exact(&mut payload).unwrap();
+            sender
+                .send((message_type, String::from_utf8(payload).unwrap()))
+                .unwrap();
+
+            let mut message = b"i3-ipc".to_vec();
+            message.extend((reply.len() as u32).to_ne_bytes());
+            message.extend(message_type.to_ne_bytes());
+            message.extend(reply.as_bytes());
+            stream.write_all(&message).unwrap();
+        }
+    });
+
+    (Compositor::Sway(path), receiver)
+}
+
+#[test]
+fn hyprland_lists_mapped_clients() {
+    let (compositor, requests) = fake_hyprland("clients", vec![fixture("hyprland_clients.json")]);
+
+    let windows = compositor.get_windows().unwrap();
+
+    assert_eq!(requests.recv().unwrap(), "j/clients");
+    assert_eq!(
+        windows,
+        vec![
+            CompositorWindow {
+                id: "0x55d4c1a0f2b0".to_string(),
+                title: "Hyprland Wiki — Mozilla Firefox".to_string(),
+                class: "firefox".to_string(),
+                workspace: "1".to_string(),
+                pid: Some(2231),
+            },
+            CompositorWindow {
+                id: "0x55d4c1b3a7e0".to_string(),
+                title: "nvim ~/projects/api".to_string(),
+                class: "kitty".to_string(),
+                workspace: "3".to_string(),
+                pid: Some(2518),
+            },
+        ]
+    );
+}
+
+#[test]
+fn hyprland_dispatches_window_commands() {
+    let (compositor, requests) = fake_hyprland(
+        "dispatch",
+        vec![
+            "ok".to_string(),
+            "ok".to_string(),
+            r#"{"id": 2, "name": "2"}"#.to_string(),
+            "ok".to_string(),
+        ],
+    );
+
+    compositor.focus_window("0x55d4c1a0f2b0").unwrap();
+    compositor.close_window("0x55d4c1a0f2b0").unwrap();
+    compositor.move_window("0x55d4c1a0f2b0", None).unwrap();
+
+    assert_eq!(
+        requests.recv().unwrap(),
+        "dispatch focuswindow address:0x55d4c1a0f2b0"
+    );
+    assert_eq!(
+        requests.recv().unwrap(),
+        "dispatch closewindow address:0x55d4c1a0f2b0"
+    );
+    assert_eq!(requests.recv().unwrap(), "j/activeworkspace");
+    assert_eq!(
+        requests.recv().unwrap(),
+        "dispatch movetoworkspacesilent 2,address:0x55d4c1a0f2b0"
+    );
+}
+
+#[test]
+fn hyprland_errors_are_reported() {
+    let (compositor, _requests) = fake_hyprland("error", vec!["No such window".to_string()]);
+
+    let error = compositor.focus_window("0x0").unwrap_err();
+
+    assert_eq!(error.to_string(), "Hyprland: No such window");
+}
+
+#[test]
+fn sway_lists_windows_with_their_workspace() {
+    let (compositor, requests) = fake_sway("tree", vec![fixture("sway_tree.json")]);
+
+    let windows = compositor.get_windows().unwrap();
+
+    assert_eq!(requests.recv().unwrap(), (4, String::new()));
+
+    let summary: Vec<(&str, &str, &str)> = windows
+        .iter()
+        .map(|window| {
+            (
+                window.id.as_str(),
+                window.class.as_str(),
+                window.workspace.as_str(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            ("7", "firefox", "1"),
+            ("10", "kitty", "1"),
+            ("14", "steam", "2: chat")
+        ]
+    );
+}
+
+#[test]
+fn sway_runs_commands_on_the_container() {
+    let (compositor, requests) = fake_sway(
+        "command",
+        vec![
+            r#"[{"success": true}]"#.to_string(),
+            r#"[{"success": false, "error": "No matching node"}]"#.to_string(),
+        ],
+    );
+
+    compositor.move_window("10", Some("2: chat")).unwrap();
+    let error = compositor.close_window("99").unwrap_err();
+
+    assert_eq!(
+        requests.recv().unwrap(),
+        (
+            0,
+            "[con_id=10] move container to workspace 2: chat".to_string()
+        )
+    );
+    assert_eq!(
+        requests.recv().unwrap(),
+        (0, "[con_id=99] kill".to_string())
+    );
+    assert_eq!(error.to_string(), "sway: No matching node");
+}
+
+#[test]
+fn plugin_filters_windows_and_moves_with_a_workspace() {
+    let (compositor, _requests) = fake_hyprland(
+        "plugin",
+        vec![
+            fixture("hyprland_clients.json"),
+            fixture("hyprland_clients.json"),
+        ],
+    );
+
+    let sniffer = Sniffer::new();
+
+    let results = get_windows_results(&compositor, &[], &sniffer, "nvim").unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].text, "nvim ~/projects/api");
+    assert_eq!(
+        results[0].secondary_text.as_deref(),
+        Some("kitty · Workspace 3")
+    );
+
+    let results = get_windows_results(&compositor, &[], &sniffer, "firefox >4").unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0].text,
+        "Move Hyprland Wiki — Mozilla Firefox to workspace 4"
+    );
+}
@@ -2,10 +2,41 @@ export interface TUIResult {
 	icon_path: string | null,
 	text: string,
 	secondary_text: string | null,
-	action: OpenApp | OpenFile | OpenURL | CopyText | CopyImage | ShowResults | Custom | OpenSettings | Session | Bookmark | null,
+	action: OpenApp | OpenFile | OpenURL | CopyText | CopyImage | ShowResults | Custom | OpenSettings | Session | Bookmark | OpenDesktopAction | OpenWith | OpenAppWithArgs | OpenAppWithProfile | SetDefaultProfile | SetDefaultApp | ShowInFolder | CopyFile | FocusWindow | MoveWindow | CloseWindow | PinClipboardEntry | DeleteClipboardEntry | ClearClipboardHistory | TypeText | null,
+	actions?: SecondaryAction[],
+	badge?: string | null,
+	/** Matched [start, end) ranges of the text, in code points */
+	highlights?: [number, number][],
 	info: string
 }
 
+/** Splits the text into matched and unmatched parts for highlighting */
+export function getTextParts(result: TUIResult): { text: string, matched: boolean }[] {
+	let chars = Array.from(result.text);
+	let parts: { text: string, matched: boolean }[] = [];
+	let position = 0;
+
+	for (let [start, end] of result.highlights ?? []) {
+		if (start > position) {
+			parts.push({ text: chars.slice(position, start).join(""), matched: false });
+		}
+
+		parts.push({ text: chars.slice(start, end).join(""), matched: true });
+		position = end;
+	}
+
+	if (position < chars.length) {
+		parts.push({ text: chars.slice(position).join(""), matched: false });
+	}
+
+	return parts;
+}
+
+export interface SecondaryAction {
+	text: string,
+	action: NonNullable<TUIResult["action"]>
+}
+
 export interface OpenApp {
 	type: string,
 	path: string
@@ -16,6 +47,89 @@ export interface OpenFile {
 	path: string
 }
 
+export interface OpenDesktopAction {
+	type: string,
+	path: string,
+	action: string
+}
+
+export interface OpenWith {
+	type: string,
+	path: string,
+	file: string
+}
+
+export interface OpenAppWithArgs {
+	type: string,
+	path: string,
+	targets: string[]
+}
+
+export interface OpenAppWithProfile {
+	type: string,
+	path: string,
+	profile: string | null
+}
+
+export interface ShowInFolder {
+	type: string,
+	path: string
+}
+
+export interface CopyFile {
+	type: string,
+	path: string
+}
+
+export interface FocusWindow {
+	type: string,
+	id: string
+}
+
+export interface MoveWindow {
+	type: string,
+	id: string,
+	workspace: string | null
+}
+
+export interface CloseWindow {
+	type: string,
+	id: string
+}
+
+export interface PinClipboardEntry {
+	type: string,
+	id: string,
+	pinned: boolean
+}
+
+export interface DeleteClipboardEntry {
+	type: string,
+	id: string
+}
+
+export interface ClearClipboardHistory {
+	type: string
+}
+
+export interface TypeText {
+	type: string,
+	text: string,
+	mode?: "Type" | "Paste"
+}
+
+export interface SetDefaultProfile {
+	type: string,
+	path: string,
+	profile: string | null
+}
+
+export interface SetDefaultApp {
+	type: string,
+	mime_type: string,
+	id: string
+}
+
 export interface OpenURL {
 	type: string,
 	url: string
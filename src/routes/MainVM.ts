@@ -1,26 +1,58 @@
-import type { TUIResult } from "$lib/features/result/TUIResult";
+import type { SecondaryAction, TUIResult } from "$lib/features/result/TUIResult";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { get, writable } from "svelte/store";
 
-export type MainScreenAction = { action: "arrow-up" } | { action: "arrow-down" } | { action: "search-input", text: string } | { action: "enter-press" } | { action: "escape-press" } | { action: "result-hover", index: number } | { action: "result-click" };
+export type MainScreenAction = { action: "arrow-up" } | { action: "arrow-down" } | { action: "search-input", text: string } | { action: "enter-press" } | { action: "alt-enter-press" } | { action: "escape-press" } | { action: "backspace-press" } | { action: "result-hover", index: number } | { action: "result-click" };
 
 export class MainVM {
 	state = writable<{
 		text: string;
 		currentIndex: number;
 		results: TUIResult[];
+		error: string | null;
+		actionsOpen: boolean;
+		actionIndex: number;
+		actions: SecondaryAction[];
 	}>({
 		text: "",
 		currentIndex: 0,
-		results: []
+		results: [],
+		error: null,
+		actionsOpen: false,
+		actionIndex: 0,
+		actions: []
 	});
 
+	/** ID of the newest query, results of older ones are ignored */
+	private queryId = 0;
+	private shownQueryId = 0;
+
 	constructor() {
-		listen<{ results: TUIResult[] }>("show-plugin-results", (e) => {
-			let results: TUIResult[] = e.payload.results;
-			this.state.update(state => ({ ...state, currentIndex: 0, results: results }));
+		// The backend may still know the query IDs of the page before a reload
+		invoke("invoke_reset_search").catch((error) => {
+			console.error(error);
+		});
+
+		listen<{ query_id: number; results: TUIResult[]; pending: boolean; error: string | null }>("show-plugin-results", (e) => {
+			let payload = e.payload;
+
+			if (payload.query_id !== this.queryId) {
+				return;
+			}
+
+			// Later results of the same query keep the selection where it is
+			let isNewQuery = payload.query_id !== this.shownQueryId;
+			this.shownQueryId = payload.query_id;
+
+			this.state.update(state => ({
+				...state,
+				results: payload.results,
+				currentIndex: isNewQuery ? 0 : Math.min(state.currentIndex, Math.max(payload.results.length - 1, 0)),
+				error: payload.error,
+				actionsOpen: isNewQuery ? false : state.actionsOpen
+			}));
 		})
 	}
 
@@ -46,11 +78,21 @@ export class MainVM {
 				break;
 			}
 
+			case "alt-enter-press": {
+				this.onAltEnterPress();
+				break;
+			}
+
 			case "escape-press": {
 				this.onEscapePress();
 				break;
 			}
 
+			case "backspace-press": {
+				this.onBackspacePress();
+				break;
+			}
+
 			case "result-hover": {
 				this.onResultHover(action.index)
 				break;
@@ -64,6 +106,11 @@ export class MainVM {
 	}
 
 	private onArrowUp() {
+		if (get(this.state).actionsOpen) {
+			this.state.update(state => ({ ...state, actionIndex: Math.max(state.actionIndex - 1, 0) }));
+			return;
+		}
+
 		let newIndex = get(this.state).currentIndex - 1;
 
 		if (newIndex < 0) {
@@ -76,6 +123,12 @@ export class MainVM {
 	}
 
 	private onArrowDown() {
+		if (get(this.state).actionsOpen) {
+			let actionsCount = this.getCurrentActions().length;
+			this.state.update(state => ({ ...state, actionIndex: Math.min(state.actionIndex + 1, actionsCount - 1) }));
+			return;
+		}
+
 		let newIndex = get(this.state).currentIndex + 1;
 
 		if (newIndex >= get(this.state).results.length) {
@@ -103,38 +156,120 @@ export class MainVM {
 		}
 	}
 
-	private async onSearchInput(text: string) {
-		let results: TUIResult[] = await invoke("invoke_search", { text: text });
+	private onSearchInput(text: string) {
+		this.queryId += 1;
+		this.state.update(state => ({ ...state, text: text }));
 
-		this.state.update(state => ({ ...state, results: results, text: text, currentIndex: 0 }));
+		invoke("invoke_search", { text: text, queryId: this.queryId }).catch((error) => {
+			this.state.update(state => ({ ...state, error: `${error}` }));
+		});
 	}
 
 	private onEnterPress() {
+		let state = get(this.state);
+
+		if (state.actionsOpen) {
+			let secondaryAction = this.getCurrentActions()[state.actionIndex];
+
+			if (secondaryAction) {
+				this.runAction(secondaryAction.action);
+			}
+
+			return;
+		}
+
 		this.runResult();
 	}
 
+	/** Opens the secondary actions of the selected result */
+	private async onAltEnterPress() {
+		let state = get(this.state);
+		let result = state.results[state.currentIndex];
+
+		if (state.actionsOpen || !result) {
+			this.state.update(state => ({ ...state, actionsOpen: false }));
+			return;
+		}
+
+		let actions: SecondaryAction[] = await invoke("invoke_get_result_actions", { result: result });
+
+		if (actions.length > 0) {
+			this.state.update(state => ({ ...state, actionsOpen: true, actionIndex: 0, actions: actions }));
+		}
+	}
+
+	private getCurrentActions(): SecondaryAction[] {
+		return get(this.state).actions;
+	}
+
 	private runResult() {
 		let state = get(this.state);
 		let index = state.currentIndex;
 		let result = state.results[index];
 
 		if (result.action) {
-			this.resetState();
-			invoke("invoke_result_action", { action: result.action });
+			this.runAction(result.action);
 		}
 	}
 
-	private onEscapePress() {
-		getCurrentWindow().close();
+	private runAction(action: SecondaryAction["action"]) {
 		this.resetState();
+		invoke("invoke_result_action", { action: action }).catch((error) => {
+			this.state.update(state => ({ ...state, error: `${error}` }));
+		});
+	}
+
+	/** Closes the actions, then clears the query, then goes back one level or closes the window */
+	private onEscapePress() {
+		let state = get(this.state);
+
+		if (state.actionsOpen) {
+			this.state.update(state => ({ ...state, actionsOpen: false }));
+			return;
+		}
+
+		if (state.text !== "") {
+			this.onSearchInput("");
+			return;
+		}
+
+		this.navigateBack().then((wentBack) => {
+			if (!wentBack) {
+				getCurrentWindow().close();
+				this.resetState();
+			}
+		});
+	}
+
+	/** Goes back to the previous level of results when the query is empty */
+	private onBackspacePress() {
+		let state = get(this.state);
+
+		if (state.actionsOpen || state.text !== "") {
+			return;
+		}
+
+		this.navigateBack();
+	}
+
+	/** Resolves to false when already at the top level */
+	private async navigateBack(): Promise<boolean> {
+		this.state.update(state => ({ ...state, text: "" }));
+
+		try {
+			return await invoke<boolean>("invoke_navigate_back");
+		} catch (error) {
+			this.state.update(state => ({ ...state, error: `${error}` }));
+			return true;
+		}
 	}
 
 	private resetState() {
-		this.state.set({ text: "", results: [], currentIndex: 0 });
+		this.state.set({ text: "", results: [], currentIndex: 0, error: null, actionsOpen: false, actionIndex: 0, actions: [] });
 	}
 
 	private onResultHover(index: number) {
-		this.state.update(state => ({ ...state, currentIndex: index }));
+		this.state.update(state => ({ ...state, currentIndex: index, actionsOpen: false }));
 	}
 
 	private onResultClick() {